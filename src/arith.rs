@@ -1,3 +1,7 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{One, Zero};
 
 pub fn gcd(a: i32, b: i32) -> i32 {
     let a = a.abs();
@@ -29,3 +33,68 @@ pub fn lcm(a: i32, b: i32) -> i32 {
 pub fn lcm_vec(vec: &Vec<i32>) -> i32 {
     vec.iter().fold(1, |a, b|{ lcm(a, *b) })
 }
+
+/// Scales `vec` to the unique vector of coprime integers that has the same direction
+///
+/// A vector containing only zeros is mapped to a vector of zeros
+pub fn to_primitive_integers(vec: &[BigRational]) -> Vec<BigInt> {
+    let denoms_lcm = vec.iter().fold(BigInt::one(), |acc, coef| { acc.lcm(coef.denom()) });
+    let scaled: Vec<BigInt> = vec.iter()
+        .map(|coef| { (coef * &denoms_lcm).to_integer() })
+        .collect();
+    let numers_gcd = scaled.iter().fold(BigInt::zero(), |acc, coef| { acc.gcd(coef) });
+    if numers_gcd.is_zero() {
+        scaled
+    } else {
+        scaled.iter().map(|coef| { coef / &numers_gcd }).collect()
+    }
+}
+
+/// Returns all the sequences of `n_parts` positive integers that sum up to `sum`, in lexicographic order
+///
+/// The sequences are generated lazily, as their number grows combinatorially with `n_parts`
+pub fn compositions(sum: i64, n_parts: usize) -> Compositions {
+    let next = if n_parts == 0 {
+        if sum == 0 { Some(Vec::new()) } else { None }
+    } else if sum >= n_parts as i64 {
+        let mut first = vec![1; n_parts];
+        first[n_parts - 1] = sum - (n_parts as i64 - 1);
+        Some(first)
+    } else {
+        None
+    };
+    Compositions { next }
+}
+
+/// Iterator over the compositions of an integer, see `compositions`
+pub struct Compositions {
+    next: Option<Vec<i64>>,
+}
+
+impl Iterator for Compositions {
+    type Item = Vec<i64>;
+
+    fn next(&mut self) -> Option<Vec<i64>> {
+        let curr = self.next.take()?;
+        let n_parts = curr.len();
+        // the successor increments the last part that can grow, i.e. whose following parts are not all 1,
+        // and moves the rest of the sum to the last part
+        if n_parts >= 2 {
+            let mut suffix_sum = curr[n_parts - 1];
+            for idx in (0..(n_parts - 1)).rev() {
+                if suffix_sum > (n_parts - 1 - idx) as i64 {
+                    let mut succ = curr.clone();
+                    succ[idx] += 1;
+                    for part in succ.iter_mut().take(n_parts - 1).skip(idx + 1) {
+                        *part = 1;
+                    }
+                    succ[n_parts - 1] = suffix_sum - 1 - (n_parts - 2 - idx) as i64;
+                    self.next = Some(succ);
+                    break;
+                }
+                suffix_sum += curr[idx];
+            }
+        }
+        Some(curr)
+    }
+}
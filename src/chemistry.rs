@@ -3,43 +3,92 @@ use std::collections::HashSet;
 use std::fmt;
 use std::fmt::{Display, Formatter};
 use std::hash::Hash;
-use crate::arith::lcm_vec;
-use crate::chemistry::ChemUnit::{Gram, Milligram, Mol};
-use crate::lin_alg::Matrix;
-use crate::parsing::PositionedError;
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, ToPrimitive};
+use num_integer::Integer;
+use crate::arith::{compositions, to_primitive_integers};
+use crate::lin_alg::{has_positive_combination, Matrix};
+use crate::error::{ChemError, Span};
 use crate::return_on_error;
 
 #[derive(PartialEq, Eq, Hash, Ord, PartialOrd, Debug, Clone)]
 pub struct Atom {
     pub code: String,
     pub name: String,
-    pub atomic_mass_milli_amu: u64,
+    /// exact atomic mass, in units of 10^-9 u (finer than any standard atomic weight)
+    pub atomic_mass_nano_amu: u64,
+    /// standard uncertainty of the atomic mass, in units of 10^-9 u
+    pub atomic_mass_uncertainty_nano_amu: u64,
 }
 
+pub const NANO_AMU_PER_AMU: u64 = 1_000_000_000;
+
 impl Atom {
     pub fn atomic_mass_amu(&self) -> f64 {
-        (self.atomic_mass_milli_amu as f64) / 1000.0
+        (self.atomic_mass_nano_amu as f64) / (NANO_AMU_PER_AMU as f64)
+    }
+
+    pub fn atomic_mass_uncertainty_amu(&self) -> f64 {
+        (self.atomic_mass_uncertainty_nano_amu as f64) / (NANO_AMU_PER_AMU as f64)
     }
 }
 
+pub const ELECTRON_REPR: &str = "e^-";
+
 #[derive(Debug, Eq, PartialEq, Clone, Hash)]
 pub struct Molecule {
     pub atoms: BTreeMap<Atom, u32>,
     pub charge: i32,
     pub string_repr: Option<String>,
+    /// state of matter, if specified (e.g. `(aq)` in `NaCl(aq)`)
+    pub phase: Option<Phase>,
 }
 
 impl Molecule {
-    pub fn mass_milli_amu(&self) -> u64 {
+    /// Electron, as a species of half-reactions (its mass is neglected)
+    pub fn electron() -> Molecule {
+        Molecule {
+            atoms: BTreeMap::new(),
+            charge: -1,
+            string_repr: Some(ELECTRON_REPR.to_string()),
+            phase: None,
+        }
+    }
+
+    pub fn is_electron(&self) -> bool {
+        self.atoms.is_empty() && self.charge == -1
+    }
+
+    /// `true` for H2O, whatever its phase
+    pub fn is_water(&self) -> bool {
+        let count_of = |code: &str| { self.atoms.iter().find(|(atom, _)| { atom.code == code }).map(|(_, &count)| { count }) };
+        self.charge == 0 && self.atoms.len() == 2 && count_of("H") == Some(2) && count_of("O") == Some(1)
+    }
+
+    pub fn mass_nano_amu(&self) -> u64 {
         let mut sum: u64 = 0;
         for (atom, &coef) in &self.atoms {
-            sum += (coef as u64) * atom.atomic_mass_milli_amu;
+            sum += (coef as u64) * atom.atomic_mass_nano_amu;
         }
         sum
     }
 
     pub fn mass_amu(&self) -> f64 {
-        (self.mass_milli_amu() as f64) / 1000.0
+        (self.mass_nano_amu() as f64) / (NANO_AMU_PER_AMU as f64)
+    }
+
+    /// Uncertainty of the molecular mass
+    ///
+    /// The atoms of a same element are fully correlated (their uncertainties add up), while the uncertainties of
+    /// different elements are independent (they are summed in quadrature)
+    pub fn mass_uncertainty_amu(&self) -> f64 {
+        let mut sum_of_squares: f64 = 0.0;
+        for (atom, &coef) in &self.atoms {
+            let elem_uncertainty = (coef as f64) * atom.atomic_mass_uncertainty_amu();
+            sum_of_squares += elem_uncertainty * elem_uncertainty;
+        }
+        sum_of_squares.sqrt()
     }
 
     fn default_fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
@@ -57,15 +106,56 @@ impl Molecule {
 impl fmt::Display for Molecule {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         if let Some(string_repr) = &self.string_repr {
-            write!(f, "{}", string_repr)
+            return_on_error!(write!(f, "{}", string_repr));
         } else {
-            self.default_fmt(f)
+            return_on_error!(self.default_fmt(f));
         }
+        if let Some(phase) = self.phase {
+            return_on_error!(write!(f, "({})", phase.symbol()));
+        }
+        Ok(())
     }
 }
 
 pub type PeriodicTable = BTreeMap<String, Atom>;
 
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum Phase {
+    Solid,
+    Liquid,
+    Gas,
+    /// dissolved in water
+    Aqueous,
+}
+
+impl Phase {
+    /// Symbol used to annotate species, e.g. `aq` in `NaCl(aq)`
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Phase::Solid => "s",
+            Phase::Liquid => "l",
+            Phase::Gas => "g",
+            Phase::Aqueous => "aq"
+        }
+    }
+
+    pub fn of_symbol(symbol: &str) -> Option<Phase> {
+        [Phase::Solid, Phase::Liquid, Phase::Gas, Phase::Aqueous].into_iter().find(|phase| { phase.symbol() == symbol })
+    }
+}
+
+impl Display for Phase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let repres = match self {
+            Phase::Solid => "solid",
+            Phase::Liquid => "liquid",
+            Phase::Gas => "gas",
+            Phase::Aqueous => "aqueous"
+        };
+        write!(f, "{}", repres)
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct RawEquation {
     pub lhs: Vec<Molecule>,
@@ -75,7 +165,7 @@ pub struct RawEquation {
 
 impl RawEquation {
     pub fn all_atoms_ordered(&self) -> Vec<Atom> {
-        self.lhs.iter().flat_map(|molec| { molec.atoms.keys().cloned() }).collect()
+        self.lhs.iter().chain(self.rhs.iter()).flat_map(|molec| { molec.atoms.keys().cloned() }).collect()
     }
 
     pub fn all_atoms_set(&self) -> HashSet<Atom> {
@@ -93,6 +183,84 @@ impl Display for RawEquation {
     }
 }
 
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum EquationSide {
+    Reactants,
+    Products,
+}
+
+impl Display for EquationSide {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let repres = match self {
+            EquationSide::Reactants => "reactants",
+            EquationSide::Products => "products"
+        };
+        write!(f, "{}", repres)
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+/// An element that appears in a single member of an equation, i.e. that the reaction would create or destroy
+pub struct UnconservedElement {
+    pub atom: Atom,
+    /// the member in which the element appears
+    pub side: EquationSide,
+    /// position of the first occurrence of the element in the input string, only known when the element was located by
+    /// `parsing::locate_unconserved_elements`
+    pub position: Option<u64>,
+}
+
+impl UnconservedElement {
+    /// Returns the span of the first occurrence of the element in the input string, if known
+    pub fn span(&self) -> Option<Span> {
+        self.position.map(|pos| { pos..(pos + self.atom.code.chars().count() as u64) })
+    }
+}
+
+impl Display for UnconservedElement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let effect = match self.side {
+            EquationSide::Reactants => "destroyed",
+            EquationSide::Products => "created"
+        };
+        return_on_error!(write!(f, "{} only appears in the {} ({})", self.atom.code, self.side, effect));
+        if let Some(pos) = self.position {
+            return_on_error!(write!(f, " at position {}", pos));
+        }
+        Ok(())
+    }
+}
+
+/// Returns the elements that appear in only one member of `raw_eq`, in order of first appearance
+///
+/// Their positions are `None`, as `raw_eq` does not know the string it was parsed from: use
+/// `parsing::check_element_conservation` (or `parsing::locate_unconserved_elements`) to locate them in the input string
+pub fn unconserved_elements(raw_eq: &RawEquation) -> Vec<UnconservedElement> {
+    let atoms_in = |member: &Vec<Molecule>| -> HashSet<Atom> {
+        member.iter().flat_map(|molec| { molec.atoms.keys().cloned() }).collect()
+    };
+    let lhs_atoms = atoms_in(&raw_eq.lhs);
+    let rhs_atoms = atoms_in(&raw_eq.rhs);
+    let mut unconserved: Vec<UnconservedElement> = Vec::new();
+    for atom in raw_eq.all_atoms_ordered() {
+        let side = match (lhs_atoms.contains(&atom), rhs_atoms.contains(&atom)) {
+            (true, false) => EquationSide::Reactants,
+            (false, true) => EquationSide::Products,
+            _ => continue
+        };
+        if !unconserved.iter().any(|elem| { elem.atom == atom }) {
+            unconserved.push(UnconservedElement { atom, side, position: None });
+        }
+    }
+    unconserved
+}
+
+/// Fails if an element of `raw_eq` appears in only one of its members
+fn check_conservation(raw_eq: &RawEquation) -> Result<(), ChemError> {
+    let unconserved = unconserved_elements(raw_eq);
+    if unconserved.is_empty() { Ok(()) } else { Err(ChemError::UnconservedElements(unconserved)) }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct BalancedEquation {
     pub lhs: Vec<(Molecule, i32)>,
@@ -115,42 +283,397 @@ impl Display for BalancedEquation {
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
-pub enum ChemUnit {
-    Gram,
-    Milligram,
-    Mol,
+/// Avogadro constant, in particles per mol
+pub const AVOGADRO_CONSTANT: f64 = 6.022_140_76e23;
+
+/// Molar gas constant, in J/(mol·K)
+pub const GAS_CONSTANT: f64 = 8.314_462_618;
+
+/// Temperature and pressure of the gases of a reaction, assumed to be ideal gases
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GasConditions {
+    /// in K
+    pub temperature: f64,
+    /// in Pa
+    pub pressure: f64,
 }
 
-impl Display for ChemUnit {
+/// Supported temperature units, with the offset to add to a value in this unit to get K
+const TEMPERATURE_UNITS: [(&str, f64); 3] = [("K", 0.0), ("°C", 273.15), ("C", 273.15)];
+
+/// Supported pressure units, with their value in Pa
+const PRESSURE_UNITS: [(&str, f64); 6] = [
+    ("Pa", 1.0),
+    ("kPa", 1_000.0),
+    ("bar", 100_000.0),
+    ("atm", 101_325.0),
+    ("mmHg", 133.322_387_415),
+    ("Torr", 101_325.0 / 760.0),
+];
+
+impl GasConditions {
+    /// Standard temperature and pressure: 0 °C and 1 atm, at which the molar volume is 22.414 L/mol
+    pub const STP: GasConditions = GasConditions { temperature: 273.15, pressure: 101_325.0 };
+
+    /// Volume of one mol of gas, in L, given by PV = nRT
+    pub fn molar_volume(&self) -> f64 {
+        1000.0 * GAS_CONSTANT * self.temperature / self.pressure
+    }
+}
+
+impl Display for GasConditions {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.2} K, {:.3} kPa", self.temperature, self.pressure / 1000.0)
+    }
+}
+
+/// Converts a temperature in the given unit (`K` or `°C`) to K, returns `None` if the unit is unknown or if the
+/// temperature is not above absolute zero
+pub fn temperature_for(value: f64, unit: &str) -> Option<f64> {
+    let (_, offset) = TEMPERATURE_UNITS.iter().find(|(symbol, _)| { *symbol == unit })?;
+    Some(value + offset).filter(|kelvins| { *kelvins > 0.0 })
+}
+
+/// Converts a pressure in the given unit (e.g. `atm` or `kPa`) to Pa, returns `None` if the unit is unknown
+pub fn pressure_for(value: f64, unit: &str) -> Option<f64> {
+    PRESSURE_UNITS.iter().find(|(symbol, _)| { *symbol == unit }).map(|(_, pascals)| { value * pascals })
+}
+
+/// Physical dimension of a unit, quantities of the same dimension can be converted into each other
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Dimension {
+    Mass,
+    /// amount of substance, in mol or in number of particles
+    Amount,
+    Volume,
+}
+
+impl Display for Dimension {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         let repres = match self {
-            Gram => "g",
-            Milligram => "mg",
-            Mol => "mol"
+            Dimension::Mass => "mass",
+            Dimension::Amount => "amount",
+            Dimension::Volume => "volume"
         };
         write!(f, "{}", repres)
     }
 }
 
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum BaseUnit {
+    Gram,
+    Pound,
+    Ounce,
+    Mol,
+    /// number of particles (molecules, atoms or ions)
+    Particle,
+    Liter,
+    CubicMeter,
+}
+
+impl BaseUnit {
+    /// Units without prefix, the first symbol of each unit is used to display it
+    const SYMBOLS: [(&'static str, BaseUnit); 10] = [
+        ("g", BaseUnit::Gram),
+        ("lb", BaseUnit::Pound),
+        ("oz", BaseUnit::Ounce),
+        ("mol", BaseUnit::Mol),
+        ("particles", BaseUnit::Particle),
+        ("molecules", BaseUnit::Particle),
+        ("L", BaseUnit::Liter),
+        ("l", BaseUnit::Liter),
+        ("m³", BaseUnit::CubicMeter),
+        ("m3", BaseUnit::CubicMeter),
+    ];
+
+    pub fn symbol(&self) -> &'static str {
+        BaseUnit::SYMBOLS.iter().find(|(_, base)| { base == self }).unwrap().0
+    }
+
+    pub fn dimension(&self) -> Dimension {
+        match self {
+            BaseUnit::Gram | BaseUnit::Pound | BaseUnit::Ounce => Dimension::Mass,
+            BaseUnit::Mol | BaseUnit::Particle => Dimension::Amount,
+            BaseUnit::Liter | BaseUnit::CubicMeter => Dimension::Volume
+        }
+    }
+
+    /// Value of one unit in the reference unit of its dimension: g, mol or L
+    fn reference_factor(&self) -> f64 {
+        match self {
+            BaseUnit::Gram | BaseUnit::Mol | BaseUnit::Liter => 1.0,
+            BaseUnit::Pound => 453.592_37,
+            BaseUnit::Ounce => 28.349_523_125,
+            BaseUnit::Particle => 1.0 / AVOGADRO_CONSTANT,
+            BaseUnit::CubicMeter => 1000.0
+        }
+    }
+
+    fn accepts_prefix(&self) -> bool {
+        matches!(self, BaseUnit::Gram | BaseUnit::Mol | BaseUnit::Liter | BaseUnit::CubicMeter)
+    }
+}
+
+/// SI prefix, e.g. `m` in `mg`
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Prefix {
+    Nano,
+    Micro,
+    Milli,
+    Centi,
+    Deci,
+    Kilo,
+}
+
+impl Prefix {
+    /// The first symbol of each prefix is used to display it, `u` is accepted for `µ`
+    const SYMBOLS: [(&'static str, Prefix); 8] = [
+        ("n", Prefix::Nano),
+        ("µ", Prefix::Micro),
+        ("μ", Prefix::Micro),
+        ("u", Prefix::Micro),
+        ("m", Prefix::Milli),
+        ("c", Prefix::Centi),
+        ("d", Prefix::Deci),
+        ("k", Prefix::Kilo),
+    ];
+
+    pub fn symbol(&self) -> &'static str {
+        Prefix::SYMBOLS.iter().find(|(_, prefix)| { prefix == self }).unwrap().0
+    }
+
+    pub fn factor(&self) -> f64 {
+        match self {
+            Prefix::Nano => 1e-9,
+            Prefix::Micro => 1e-6,
+            Prefix::Milli => 1e-3,
+            Prefix::Centi => 1e-2,
+            Prefix::Deci => 1e-1,
+            Prefix::Kilo => 1e3
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct ChemUnit {
+    pub prefix: Option<Prefix>,
+    pub base: BaseUnit,
+}
+
+impl ChemUnit {
+    pub const GRAM: ChemUnit = ChemUnit { prefix: None, base: BaseUnit::Gram };
+    pub const MILLIGRAM: ChemUnit = ChemUnit { prefix: Some(Prefix::Milli), base: BaseUnit::Gram };
+    pub const MOL: ChemUnit = ChemUnit { prefix: None, base: BaseUnit::Mol };
+    pub const LITER: ChemUnit = ChemUnit { prefix: None, base: BaseUnit::Liter };
+
+    pub fn dimension(&self) -> Dimension {
+        self.base.dimension()
+    }
+
+    /// Value of one unit in the reference unit of its dimension: g, mol or L
+    ///
+    /// The prefix of a cubic unit is cubed, e.g. 1 cm³ is 10⁻⁶ m³
+    pub fn reference_factor(&self) -> f64 {
+        let prefix_factor = self.prefix.map_or(1.0, |prefix| { prefix.factor() });
+        let prefix_factor = if self.base == BaseUnit::CubicMeter { prefix_factor.powi(3) } else { prefix_factor };
+        prefix_factor * self.base.reference_factor()
+    }
+}
+
+impl Display for ChemUnit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.prefix.map_or("", |prefix| { prefix.symbol() }), self.base.symbol())
+    }
+}
+
+/// Reads a unit, given as a base unit (e.g. `g` or `lb`) or as an SI prefix followed by a base unit (e.g. `mg` or `kmol`)
 pub fn chem_unit_for(txt: &String) -> Result<ChemUnit, ()> {
-    match txt.as_str() {
-        "g" => Ok(Gram),
-        "mg" => Ok(Milligram),
-        "mol" => Ok(Mol),
-        _ => Err(())
+    let base_unit_for = |base_str: &str| -> Option<BaseUnit> {
+        BaseUnit::SYMBOLS.iter().find(|(symbol, _)| { *symbol == base_str }).map(|(_, base)| { *base })
+    };
+    if let Some(base) = base_unit_for(txt) {
+        return Ok(ChemUnit { prefix: None, base });
     }
+    Prefix::SYMBOLS.iter()
+        .filter_map(|(symbol, prefix)| {
+            let base = base_unit_for(txt.strip_prefix(symbol)?)?;
+            if base.accepts_prefix() { Some(ChemUnit { prefix: Some(*prefix), base }) } else { None }
+        })
+        .next()
+        .ok_or(())
 }
 
 #[derive(Debug, Clone)]
-pub struct ChemQuantity(pub f64, pub ChemUnit);
+/// Value, unit and standard uncertainty (in the same unit, 0 for an exact quantity)
+pub struct ChemQuantity(pub f64, pub ChemUnit, pub f64);
 
 impl Display for ChemQuantity {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "{:.3} {}", self.0, self.1)
+        write!(f, "{} {}", format_with_uncertainty(self.0, self.2), self.1)
+    }
+}
+
+/// Formats `value` as `value ± uncertainty` with enough decimals to show 2 significant digits of the uncertainty if it
+/// is not 0, o.w. as an exact value with up to 6 significant digits (e.g. `0.0025` for 2.5e-3)
+///
+/// At least 3 decimals are shown, unless the value is below 1e-3 or at least 1e6, which is then written in scientific
+/// notation (e.g. `3.32107e-24` or `(3.3211 ± 0.0012)e-24`)
+pub fn format_with_uncertainty(value: f64, uncertainty: f64) -> String {
+    let magnitude = value.abs().max(uncertainty);
+    if magnitude.is_finite() && magnitude > 0.0 && !(1e-3..1e6).contains(&magnitude) {
+        return format_scientific(value, uncertainty, magnitude.log10().floor() as i32);
+    }
+    if uncertainty > 0.0 {
+        let n_decimals = (1 - uncertainty.log10().floor() as i32).clamp(3, 12) as usize;
+        format!("{:.*} ± {:.*}", n_decimals, value, n_decimals, uncertainty)
+    } else if value != 0.0 && value.is_finite() {
+        let n_decimals = (5 - value.abs().log10().floor() as i32).clamp(3, 12) as usize;
+        let formatted = format!("{:.*}", n_decimals, value);
+        // the trailing zeros beyond the 3rd decimal carry no information for an exact value
+        let n_trailing_zeros = formatted.len() - formatted.trim_end_matches('0').len();
+        formatted[..(formatted.len() - n_trailing_zeros.min(n_decimals - 3))].to_string()
+    } else {
+        format!("{:.3}", value)
+    }
+}
+
+/// Formats `value` and `uncertainty` as multiples of `10^exponent`, with the significant digits of
+/// `format_with_uncertainty`
+fn format_scientific(value: f64, uncertainty: f64, exponent: i32) -> String {
+    let scale = 10f64.powi(exponent);
+    if uncertainty > 0.0 {
+        let scaled_uncertainty = uncertainty / scale;
+        let n_decimals = (1 - scaled_uncertainty.log10().floor() as i32).clamp(0, 12) as usize;
+        format!("({:.*} ± {:.*})e{}", n_decimals, value / scale, n_decimals, scaled_uncertainty, exponent)
+    } else {
+        let mantissa = format!("{:.5}", value / scale);
+        // the trailing zeros of the mantissa carry no information for an exact value
+        format!("{}e{}", mantissa.trim_end_matches('0').trim_end_matches('.'), exponent)
+    }
+}
+
+/// Unit in which the concentration of a species in a solution is given
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum ConcentrationUnit {
+    /// mol of the species per L of solution
+    Molar,
+    /// g of the species per L of solution
+    GramPerLiter,
+    /// g of the species per 100 g of solution
+    MassPercent,
+}
+
+impl ConcentrationUnit {
+    /// Supported symbols, several symbols may denote the same unit
+    const SYMBOLS: [(&'static str, ConcentrationUnit); 4] = [
+        ("M", ConcentrationUnit::Molar),
+        ("mol/L", ConcentrationUnit::Molar),
+        ("g/L", ConcentrationUnit::GramPerLiter),
+        ("%", ConcentrationUnit::MassPercent),
+    ];
+
+    pub fn symbol(&self) -> &'static str {
+        ConcentrationUnit::SYMBOLS.iter().find(|(_, unit)| { unit == self }).unwrap().0
+    }
+
+    /// Dimension of the quantities of solution to which the concentration applies
+    pub fn solution_dimension(&self) -> Dimension {
+        match self {
+            ConcentrationUnit::Molar | ConcentrationUnit::GramPerLiter => Dimension::Volume,
+            ConcentrationUnit::MassPercent => Dimension::Mass
+        }
+    }
+}
+
+impl Display for ConcentrationUnit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.symbol())
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Value, unit and standard uncertainty (in the same unit) of the concentration of a species in a solution
+pub struct Concentration(pub f64, pub ConcentrationUnit, pub f64);
+
+impl Concentration {
+    /// Returns the quantity of the species in `solution` (a volume or a mass of solution, depending on the unit of
+    /// the concentration), in mol for a molarity and in g otherwise
+    ///
+    /// Returns `None` if `solution` is not of the dimension expected by the concentration
+    pub fn solute_quantity(&self, solution: &ChemQuantity) -> Option<ChemQuantity> {
+        let Concentration(concentration, conc_unit, conc_uncertainty) = self;
+        let ChemQuantity(value, unit, uncertainty) = solution;
+        if unit.dimension() != conc_unit.solution_dimension() {
+            return None;
+        }
+        // the solution is expressed in L or in g, the reference units of volumes and masses
+        let (solution_value, solution_uncertainty) = (value * unit.reference_factor(), uncertainty * unit.reference_factor());
+        let (factor, factor_uncertainty, solute_unit) = match conc_unit {
+            ConcentrationUnit::Molar => (*concentration, *conc_uncertainty, ChemUnit::MOL),
+            ConcentrationUnit::GramPerLiter => (*concentration, *conc_uncertainty, ChemUnit::GRAM),
+            ConcentrationUnit::MassPercent => (concentration / 100.0, conc_uncertainty / 100.0, ChemUnit::GRAM)
+        };
+        Some(ChemQuantity(
+            solution_value * factor,
+            solute_unit,
+            product_uncertainty(solution_value, solution_uncertainty, factor, factor_uncertainty),
+        ))
+    }
+}
+
+impl Display for Concentration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", format_with_uncertainty(self.0, self.2), self.1)
     }
 }
 
+/// Reads a concentration of `value` in the given unit, e.g. `M`, `mol/L`, `g/L` or `%`
+///
+/// Molarities accept the SI prefixes, e.g. `mM`, whose factor is applied to the returned value
+pub fn concentration_for(value: f64, txt: &str) -> Option<Concentration> {
+    let unit_for = |unit_str: &str| -> Option<ConcentrationUnit> {
+        ConcentrationUnit::SYMBOLS.iter().find(|(symbol, _)| { *symbol == unit_str }).map(|(_, unit)| { *unit })
+    };
+    if let Some(unit) = unit_for(txt) {
+        return Some(Concentration(value, unit, 0.0));
+    }
+    Prefix::SYMBOLS.iter()
+        .find(|(symbol, _)| { txt.strip_prefix(symbol) == Some(ConcentrationUnit::Molar.symbol()) })
+        .map(|(_, prefix)| { Concentration(value * prefix.factor(), ConcentrationUnit::Molar, 0.0) })
+}
+
+/// Molar concentration of `molec` when `qty` of it is dissolved in `volume` of solution
+pub fn molar_concentration(molec: &Molecule, qty: &ChemQuantity, volume: &ChemQuantity) -> Result<Concentration, ChemError> {
+    let ChemQuantity(n_mol, _, n_mol_uncertainty) = return_on_error!(to_mol(molec, qty));
+    if volume.1.dimension() != Dimension::Volume {
+        return Err(ChemError::UnitConversion { from: volume.1, to: ChemUnit::LITER, species: "solution".to_string() });
+    }
+    let (liters, liters_uncertainty) = (volume.0 * volume.1.reference_factor(), volume.2 * volume.1.reference_factor());
+    if liters <= 0.0 {
+        return Err(ChemError::QuantityLayout { description: "the volume of solution must be positive".to_string(), span: None });
+    }
+    // the uncertainty of the inverse of the volume is u(V) / V^2
+    Ok(Concentration(
+        n_mol / liters,
+        ConcentrationUnit::Molar,
+        product_uncertainty(n_mol, n_mol_uncertainty, 1.0 / liters, liters_uncertainty / (liters * liters)),
+    ))
+}
+
+/// Molar concentrations of the products of `mol_eq` (whose products all have a quantity) dissolved in `volume` of
+/// solution
+///
+/// Water is the solvent, and the products annotated as solids, liquids or gases are not dissolved, so they have no
+/// concentration
+pub fn products_concentrations(mol_eq: &QuantifiedEquation, volume: &ChemQuantity) -> Result<Vec<(Molecule, Concentration)>, ChemError> {
+    mol_eq.rhs.iter()
+        .filter(|(molec, _)| { !molec.is_water() && (molec.phase.is_none() || molec.phase == Some(Phase::Aqueous)) })
+        .map(|(molec, qty)| {
+            molar_concentration(molec, &qty.clone().unwrap(), volume).map(|conc| { (molec.clone(), conc) })
+        })
+        .collect()
+}
+
 #[derive(Debug, Clone)]
 pub struct QuantifiedEquation {
     pub lhs: Vec<(Molecule, Option<ChemQuantity>)>,
@@ -193,26 +716,45 @@ impl QuantifiedEquation {
         Self::has_member_all_units(&self.lhs) && Self::is_member_raw(&self.rhs)
     }
 
-    fn convert(conv_fn: fn(&Molecule, &ChemQuantity) -> ChemQuantity, member: &Vec<(Molecule, Option<ChemQuantity>)>) -> Vec<(Molecule, Option<ChemQuantity>)> {
+    /// returns `true` iff the quantity is known for exactly one species, reactant or product
+    pub fn single_quantity_known(&self) -> bool {
+        self.lhs.iter().chain(&self.rhs).filter(|(_, qty_opt)| { qty_opt.is_some() }).count() == 1
+    }
+
+    /// returns a copy of the equation in which the quantities of the products at `products_indices` are removed
+    pub fn without_products_quantities(&self, products_indices: &[usize]) -> QuantifiedEquation {
+        let rhs = self.rhs.iter().enumerate()
+            .map(|(idx, (molec, qty_opt))| {
+                (molec.clone(), if products_indices.contains(&idx) { None } else { qty_opt.clone() })
+            })
+            .collect();
+        QuantifiedEquation { lhs: self.lhs.clone(), rhs, arrow: self.arrow.clone() }
+    }
+
+    fn convert(member: &[(Molecule, Option<ChemQuantity>)], unit: ChemUnit, conditions: &GasConditions) -> Result<Vec<(Molecule, Option<ChemQuantity>)>, ChemError> {
         member.iter().map(|(molec, qty_opt)| {
-            (molec.clone(), qty_opt.clone().map(|q| { conv_fn(&molec.clone(), &q) }))
+            match qty_opt {
+                Some(q) => convert_at(molec, q, unit, conditions).map(|converted| { (molec.clone(), Some(converted)) }),
+                None => Ok((molec.clone(), None))
+            }
         }).collect()
     }
 
-    pub fn quantities_to_mol(&self) -> QuantifiedEquation {
-        QuantifiedEquation {
-            lhs: Self::convert(to_mol, &self.lhs),
-            rhs: Self::convert(to_mol, &self.rhs),
+    /// Converts all the quantities to `unit`, the gases being at `conditions`
+    pub fn quantities_to(&self, unit: ChemUnit, conditions: &GasConditions) -> Result<QuantifiedEquation, ChemError> {
+        Ok(QuantifiedEquation {
+            lhs: return_on_error!(Self::convert(&self.lhs, unit, conditions)),
+            rhs: return_on_error!(Self::convert(&self.rhs, unit, conditions)),
             arrow: self.arrow.clone(),
-        }
+        })
     }
 
-    pub fn quantities_to_grams(&self) -> QuantifiedEquation {
-        QuantifiedEquation {
-            lhs: Self::convert(to_gram, &self.lhs),
-            rhs: Self::convert(to_gram, &self.rhs),
-            arrow: self.arrow.clone(),
-        }
+    pub fn quantities_to_mol(&self) -> Result<QuantifiedEquation, ChemError> {
+        self.quantities_to(ChemUnit::MOL, &GasConditions::STP)
+    }
+
+    pub fn quantities_to_grams(&self) -> Result<QuantifiedEquation, ChemError> {
+        self.quantities_to(ChemUnit::GRAM, &GasConditions::STP)
     }
 }
 
@@ -237,24 +779,85 @@ impl Display for QuantifiedEquation {
     }
 }
 
-fn to_mol(molec: &Molecule, qty: &ChemQuantity) -> ChemQuantity {
-    let ChemQuantity(value, unit) = qty;
-    let factor = match unit {
-        Gram => molec.mass_amu(),
-        Milligram => molec.mass_milli_amu() as f64,
-        Mol => 1.0
-    };
-    ChemQuantity(value / factor, Mol)
+fn to_mol(molec: &Molecule, qty: &ChemQuantity) -> Result<ChemQuantity, ChemError> {
+    convert(molec, qty, ChemUnit::MOL)
+}
+
+/// Converts a quantity of `molec` to `unit`, the volumes of gases being taken at standard temperature and pressure
+pub fn convert(molec: &Molecule, qty: &ChemQuantity, unit: ChemUnit) -> Result<ChemQuantity, ChemError> {
+    convert_at(molec, qty, unit, &GasConditions::STP)
 }
 
-fn to_gram(molec: &Molecule, qty: &ChemQuantity) -> ChemQuantity {
-    let ChemQuantity(value, unit) = qty;
-    let factor = match unit {
-        Gram => 1.0,
-        Milligram => 0.001,
-        Mol => molec.mass_amu()
+/// Converts a quantity of `molec` to `unit`, through the molar mass of `molec` for masses and through the molar volume
+/// of an ideal gas at `conditions` for volumes
+///
+/// Volumes can only be converted to other dimensions for species that are not annotated as solid, liquid or aqueous,
+/// and masses for species that have a mass.
+/// The uncertainties of the quantity and of the molar mass are assumed to be independent
+pub fn convert_at(molec: &Molecule, qty: &ChemQuantity, unit: ChemUnit, conditions: &GasConditions) -> Result<ChemQuantity, ChemError> {
+    let ChemQuantity(value, from_unit, uncertainty) = qty;
+    let conversion_error = || { ChemError::UnitConversion { from: *from_unit, to: unit, species: molec.to_string() } };
+    // factor from the reference unit of the dimension of the quantity (g, mol or L) to that of `unit`
+    let (factor, factor_uncertainty) = if from_unit.dimension() == unit.dimension() {
+        (1.0, 0.0)
+    } else {
+        // the conversion goes through the amount, e.g. from a mass to a volume as (V_m / M) * m
+        let (to_mol, to_mol_uncertainty) = return_on_error!(mol_factor(molec, from_unit.dimension(), conditions).ok_or_else(conversion_error));
+        let (from_mol, from_mol_uncertainty) = return_on_error!(mol_factor(molec, unit.dimension(), conditions).ok_or_else(conversion_error));
+        let factor = to_mol / from_mol;
+        let relative_uncertainty = ((to_mol_uncertainty / to_mol).powi(2) + (from_mol_uncertainty / from_mol).powi(2)).sqrt();
+        (factor, factor * relative_uncertainty)
     };
-    ChemQuantity(value * factor, Gram)
+    let scale = from_unit.reference_factor() / unit.reference_factor();
+    let (scaled_value, scaled_uncertainty) = (value * scale, uncertainty * scale);
+    Ok(ChemQuantity(
+        scaled_value * factor,
+        unit,
+        product_uncertainty(scaled_value, scaled_uncertainty, factor, factor_uncertainty),
+    ))
+}
+
+/// Mass of the pure species in a mass `gross` of a sample whose mass fraction of the species is `purity` (e.g. 0.92)
+///
+/// Returns `None` if `gross` is not a mass, the purity of a sample being defined by mass
+pub fn pure_quantity(gross: &ChemQuantity, purity: f64) -> Option<ChemQuantity> {
+    if gross.1.dimension() == Dimension::Mass {
+        Some(ChemQuantity(gross.0 * purity, gross.1, gross.2 * purity))
+    } else {
+        None
+    }
+}
+
+/// Mass of the sample of the given `purity` that contains a mass `pure` of the species, inverse of `pure_quantity`
+pub fn gross_quantity(pure: &ChemQuantity, purity: f64) -> Option<ChemQuantity> {
+    if pure.1.dimension() == Dimension::Mass {
+        Some(ChemQuantity(pure.0 / purity, pure.1, pure.2 / purity))
+    } else {
+        None
+    }
+}
+
+/// Number of mol of `molec` in one reference unit (g, mol or L) of `dimension`, and its uncertainty
+///
+/// Returns `None` for a mass of a species without mass (electrons) and for a volume of a species that is not a gas
+fn mol_factor(molec: &Molecule, dimension: Dimension, conditions: &GasConditions) -> Option<(f64, f64)> {
+    match dimension {
+        Dimension::Amount => Some((1.0, 0.0)),
+        Dimension::Mass if molec.mass_nano_amu() == 0 => None,
+        Dimension::Mass => {
+            // the inverse of the molar mass, whose uncertainty is u(M) / M^2
+            let molar_mass = molec.mass_amu();
+            Some((1.0 / molar_mass, molec.mass_uncertainty_amu() / (molar_mass * molar_mass)))
+        }
+        Dimension::Volume if molec.phase.is_none() || molec.phase == Some(Phase::Gas) =>
+            Some((1.0 / conditions.molar_volume(), 0.0)),
+        Dimension::Volume => None
+    }
+}
+
+/// Uncertainty of `a * b`, for independent `a` and `b` (first-order propagation)
+fn product_uncertainty(a: f64, a_uncertainty: f64, b: f64, b_uncertainty: f64) -> f64 {
+    ((b * a_uncertainty).powi(2) + (a * b_uncertainty).powi(2)).sqrt()
 }
 
 fn matrix_for(raw_eq: &RawEquation) -> Matrix {
@@ -280,84 +883,498 @@ fn matrix_for(raw_eq: &RawEquation) -> Matrix {
     Matrix::of_row_major(&coefs)
 }
 
-fn deduce_sols(matrix: &Matrix) -> Vec<i32> {
+fn deduce_sols(matrix: &Matrix) -> Vec<BigInt> {
     assert_eq!(matrix.n_rows() + 1, matrix.n_cols());
     let diag = matrix.diagonal();
     let last_col = matrix.column(matrix.n_cols() - 1);
-    let lcm = lcm_vec(&diag);
-    let mut solution: Vec<i32> = diag.iter().zip(last_col)
-        .map(|(pivot, last_col_coef)| { -last_col_coef * lcm / pivot })
+    let mut solution: Vec<BigRational> = diag.iter().zip(last_col)
+        .map(|(pivot, last_col_coef)| { -last_col_coef / pivot })
         .collect();
-    solution.push(lcm);
-    solution
+    solution.push(BigRational::one());
+    to_primitive_integers(&solution)
 }
 
-fn solution_vec_for_balancing_of(raw_eq: &RawEquation) -> Result<Vec<i32>, PositionedError> {
+/// Converts the coefficients of a solution vector to `i32`, reporting an error if one of them does not fit
+fn to_i32_coefs(solution: &[BigInt]) -> Result<Vec<i32>, ChemError> {
+    solution.iter()
+        .map(|coef| {
+            coef.to_i32().ok_or_else(|| {
+                ChemError::CoefficientTooLarge { coefficient: coef.to_string() }
+            })
+        })
+        .collect()
+}
+
+fn solution_vec_for_balancing_of(raw_eq: &RawEquation) -> Result<Vec<i32>, ChemError> {
+    return_on_error!(check_conservation(raw_eq));
     match matrix_for(raw_eq).diagonalized().map(|matrix| { matrix.without_full_zero_rows() }) {
         Ok(matrix) => {
             let n_rows = matrix.n_rows();
             let n_cols = matrix.n_cols();
             if n_rows + 1 < n_cols {
-                Err(PositionedError(format!("solving failed, underconstrained equation"), None))
+                Err(ChemError::Underconstrained { n_independent_reactions: n_cols - n_rows })
             } else if n_rows + 1 == n_cols {
-                Ok(deduce_sols(&matrix))
+                to_i32_coefs(&deduce_sols(&matrix))
             } else {
-                Err(PositionedError(format!("cannot balance equation"), None))
+                Err(ChemError::Inconsistent)
             }
         }
-        Err(_) => Err(PositionedError(format!("cannot balance equation"), None))
+        Err(_) => Err(ChemError::Inconsistent)
     }
 }
 
-pub fn balance(raw_eq: &RawEquation) -> Result<BalancedEquation, PositionedError> {
+pub fn balance(raw_eq: &RawEquation) -> Result<BalancedEquation, ChemError> {
     let solution_vec = return_on_error!(solution_vec_for_balancing_of(raw_eq));
+    Ok(balanced_equation_for(raw_eq, &solution_vec))
+}
+
+fn balanced_equation_for(raw_eq: &RawEquation, solution_vec: &[i32]) -> BalancedEquation {
     let (lhs_sols, rhs_sols) = solution_vec.split_at(raw_eq.lhs.len());
-    Ok(BalancedEquation {
+    BalancedEquation {
         lhs: raw_eq.lhs.iter().zip(lhs_sols)
-            .map(|(molec, coef)| { (molec.clone(), coef.clone()) })
+            .map(|(molec, coef)| { (molec.clone(), *coef) })
             .collect(),
         rhs: raw_eq.rhs.iter().zip(rhs_sols)
-            .map(|(molec, coef)| { (molec.clone(), coef.clone()) })
+            .map(|(molec, coef)| { (molec.clone(), *coef) })
             .collect(),
         arrow: raw_eq.arrow.clone(),
-    })
+    }
+}
+
+/// Criterion used to choose a balancing when an equation can be balanced in several independent ways
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum BalancingCriterion {
+    /// Only accept equations that have a unique balancing
+    Unique,
+    /// Choose the balancing with only positive coefficients that has the smallest sum of coefficients
+    MinimalCoefficientsSum,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Balancing {
+    pub equation: BalancedEquation,
+    /// `true` iff the equation admits several independent balancings and `equation` was chosen among them
+    pub chosen_among_several: bool,
+}
+
+/// Largest sum of coefficients explored when searching for the balancing with the smallest sum of coefficients
+pub const MAX_SEARCHED_COEFS_SUM: i64 = 200;
+
+/// Largest number of combinations of the basis vectors tried when searching for the balancing with the smallest sum of
+/// coefficients
+pub const MAX_SEARCHED_COMBINATIONS: u64 = 1_000_000;
+
+/// Balances `raw_eq`, choosing the balancing according to `criterion` if there are several
+pub fn balance_with(raw_eq: &RawEquation, criterion: BalancingCriterion) -> Result<Balancing, ChemError> {
+    match criterion {
+        BalancingCriterion::Unique => balance(raw_eq).map(|equation| {
+            Balancing { equation, chosen_among_several: false }
+        }),
+        BalancingCriterion::MinimalCoefficientsSum => {
+            return_on_error!(check_conservation(raw_eq));
+            let basis = matrix_for(raw_eq).null_space_basis();
+            let solution_vec = match basis.len() {
+                0 => return Err(ChemError::Inconsistent),
+                1 => return_on_error!(to_i32_coefs(&to_primitive_integers(&basis[0]))),
+                _ => return_on_error!(minimal_positive_solution(&basis))
+            };
+            if solution_vec.iter().any(|&coef| { coef <= 0 }) {
+                return Err(ChemError::NoPositiveBalancing { max_coefs_sum: None });
+            }
+            Ok(Balancing {
+                equation: balanced_equation_for(raw_eq, &solution_vec),
+                chosen_among_several: basis.len() > 1,
+            })
+        }
+    }
+}
+
+/// Searches the vector with positive integer coefficients and the smallest sum of coefficients in the space spanned by
+/// `basis`, which is expected to be a basis of the null space as returned by `Matrix::null_space_basis`
+///
+/// Each vector in the space is the combination of the basis vectors whose weights are its coefficients at the free
+/// columns, so the search enumerates these weights by increasing sum. As all coefficients are at least 1, the search
+/// stops as soon as the sum of the weights plus the number of other coefficients reaches the best sum found so far, or
+/// at the first solution whose sum equals this lower bound
+///
+/// The space is first checked to contain a vector with positive coefficients, as the search would otherwise explore all
+/// the weights up to `MAX_SEARCHED_COEFS_SUM`, and the search gives up after `MAX_SEARCHED_COMBINATIONS` combinations
+fn minimal_positive_solution(basis: &[Vec<BigRational>]) -> Result<Vec<i32>, ChemError> {
+    if !has_positive_combination(basis) {
+        return Err(ChemError::NoPositiveBalancing { max_coefs_sum: None });
+    }
+    let out_of_range = ChemError::NoPositiveBalancing { max_coefs_sum: Some(MAX_SEARCHED_COEFS_SUM) };
+    let n_free = basis.len();
+    let n_coefs = basis[0].len();
+    let common_denom = basis.iter().flatten()
+        .fold(BigInt::one(), |acc, coef| { acc.lcm(coef.denom()) });
+    let scaled_basis: Vec<Vec<i64>> = match basis.iter()
+        .map(|vec| { vec.iter().map(|coef| { (coef * &common_denom).to_integer().to_i64() }).collect() })
+        .collect::<Option<Vec<Vec<i64>>>>() {
+        Some(scaled_basis) => scaled_basis,
+        None => return Err(out_of_range)
+    };
+    let common_denom = match common_denom.to_i64() {
+        Some(common_denom) => common_denom,
+        None => return Err(out_of_range)
+    };
+    let mut best: Option<(i64, Vec<i64>)> = None;
+    let mut n_combinations: u64 = 0;
+    let mut weights_sum = n_free as i64;
+    // smallest sum of the solutions whose weights sum up to `weights_sum`
+    let lower_bound = |weights_sum: i64| { weights_sum + ((n_coefs - n_free) as i64) };
+    while lower_bound(weights_sum) <= MAX_SEARCHED_COEFS_SUM
+        && best.as_ref().is_none_or(|(best_sum, _)| { lower_bound(weights_sum) < *best_sum }) {
+        for weights in compositions(weights_sum, n_free) {
+            n_combinations += 1;
+            if n_combinations > MAX_SEARCHED_COMBINATIONS {
+                return Err(ChemError::BalancingSearchLimit { max_combinations: MAX_SEARCHED_COMBINATIONS });
+            }
+            let combination: Option<Vec<i64>> = (0..n_coefs)
+                .map(|i| {
+                    weights.iter().zip(&scaled_basis).try_fold(0i64, |acc, (weight, vec)| {
+                        acc.checked_add(weight.checked_mul(vec[i])?)
+                    })
+                })
+                .collect();
+            if let Some(combination) = combination {
+                if combination.iter().all(|&coef| { coef > 0 && coef % common_denom == 0 }) {
+                    let solution: Vec<i64> = combination.iter().map(|coef| { coef / common_denom }).collect();
+                    let sum: i64 = solution.iter().sum();
+                    if best.as_ref().is_none_or(|(best_sum, _)| { sum < *best_sum }) {
+                        best = Some((sum, solution));
+                    }
+                    if sum == lower_bound(weights_sum) {
+                        break;
+                    }
+                }
+            }
+        }
+        weights_sum += 1;
+    }
+    match best {
+        Some((_, solution)) => solution.iter()
+            .map(|&coef| { i32::try_from(coef).map_err(|_| { ChemError::CoefficientTooLarge { coefficient: coef.to_string() } }) })
+            .collect(),
+        None => Err(out_of_range)
+    }
+}
+
+/// Returns a basis of the reactions that balance the species of `raw_eq`, i.e. one `BalancedEquation` per dimension of
+/// the null space of the balancing matrix
+///
+/// A species whose coefficient is negative in a basis vector is moved to the other member of the corresponding equation,
+/// and species whose coefficient is 0 are omitted
+pub fn balance_all(raw_eq: &RawEquation) -> Result<Vec<BalancedEquation>, ChemError> {
+    return_on_error!(check_conservation(raw_eq));
+    let basis = matrix_for(raw_eq).null_space_basis();
+    if basis.is_empty() {
+        return Err(ChemError::Inconsistent);
+    }
+    let mut equations: Vec<BalancedEquation> = Vec::new();
+    for vec in basis {
+        let coefs = return_on_error!(to_i32_coefs(&to_primitive_integers(&vec)));
+        equations.push(balanced_equation_for_signed_coefs(raw_eq, &coefs));
+    }
+    Ok(equations)
+}
+
+/// Builds the equation corresponding to `coefs`, moving the species with negative coefficients to the other member
+///
+/// The sign of `coefs` is chosen so that as few species as possible change member
+fn balanced_equation_for_signed_coefs(raw_eq: &RawEquation, coefs: &[i32]) -> BalancedEquation {
+    let n_moved = coefs.iter().filter(|&&coef| { coef < 0 }).count();
+    let n_kept = coefs.iter().filter(|&&coef| { coef > 0 }).count();
+    let sign = if n_moved > n_kept { -1 } else { 1 };
+    let signed_coefs: Vec<i32> = coefs.iter().map(|coef| { sign * coef }).collect();
+    balanced_equation_moving_negative_coefs(raw_eq, &signed_coefs)
+}
+
+/// Builds the equation corresponding to `coefs`, moving the species with negative coefficients to the end of the other
+/// member and omitting the species whose coefficient is 0
+fn balanced_equation_moving_negative_coefs(raw_eq: &RawEquation, coefs: &[i32]) -> BalancedEquation {
+    let (lhs_coefs, rhs_coefs) = coefs.split_at(raw_eq.lhs.len());
+    let lhs_terms: Vec<(Molecule, i32)> = raw_eq.lhs.iter().cloned().zip(lhs_coefs.iter().cloned()).collect();
+    let rhs_terms: Vec<(Molecule, i32)> = raw_eq.rhs.iter().cloned().zip(rhs_coefs.iter().cloned()).collect();
+    let kept = |terms: &Vec<(Molecule, i32)>| -> Vec<(Molecule, i32)> {
+        terms.iter().filter(|(_, coef)| { *coef > 0 }).cloned().collect()
+    };
+    let moved = |terms: &Vec<(Molecule, i32)>| -> Vec<(Molecule, i32)> {
+        terms.iter().filter(|(_, coef)| { *coef < 0 }).map(|(molec, coef)| { (molec.clone(), -coef) }).collect()
+    };
+    let lhs: Vec<(Molecule, i32)> = kept(&lhs_terms).into_iter().chain(moved(&rhs_terms)).collect();
+    let rhs: Vec<(Molecule, i32)> = kept(&rhs_terms).into_iter().chain(moved(&lhs_terms)).collect();
+    BalancedEquation { lhs, rhs, arrow: raw_eq.arrow.clone() }
 }
 
-/// returns the equation with all coefficients and the limiting reactant
-pub fn compute_lhs_coefs(quant_eq: &QuantifiedEquation) -> Result<(QuantifiedEquation, Molecule), PositionedError> {
+/// Aqueous medium of a redox reaction, which provides the species needed to balance hydrogen, oxygen and charges
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Medium {
+    /// balanced with `H^+` and `H2O`
+    Acidic,
+    /// balanced with `OH^-` and `H2O`
+    Basic,
+}
+
+impl Medium {
+    /// Returns the species provided by the medium, `H^+` or `OH^-` followed by `H2O`
+    pub fn species(&self, periodic_table: &PeriodicTable) -> Result<Vec<Molecule>, ChemError> {
+        let atom_for = |symbol: &str| {
+            periodic_table.get(symbol).cloned().ok_or_else(|| {
+                ChemError::EquationLayout {
+                    description: format!("the periodic table has no {}, which the {} medium needs", symbol, self),
+                    span: None,
+                }
+            })
+        };
+        let hydrogen = return_on_error!(atom_for("H"));
+        let oxygen = return_on_error!(atom_for("O"));
+        let species = |atoms: Vec<(&Atom, u32)>, charge: i32, repr: &str| {
+            Molecule {
+                atoms: atoms.into_iter().map(|(atom, n)| { (atom.clone(), n) }).collect(),
+                charge,
+                string_repr: Some(repr.to_string()),
+                phase: None,
+            }
+        };
+        let water = species(Vec::from([(&hydrogen, 2), (&oxygen, 1)]), 0, "H2O");
+        Ok(match self {
+            Medium::Acidic => Vec::from([species(Vec::from([(&hydrogen, 1)]), 1, "H^+"), water]),
+            Medium::Basic => Vec::from([species(Vec::from([(&oxygen, 1), (&hydrogen, 1)]), -1, "OH^-"), water])
+        })
+    }
+}
+
+impl Display for Medium {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let repres = match self {
+            Medium::Acidic => "acidic",
+            Medium::Basic => "basic"
+        };
+        write!(f, "{}", repres)
+    }
+}
+
+/// Balances the skeleton of a redox reaction (e.g. `MnO4^- + Fe^2+ => Mn^2+ + Fe^3+`), adding the species of the
+/// medium (`H^+` or `OH^-`, and `H2O`) to the member where they are needed
+///
+/// The species of `raw_eq` stay in their member, those of the medium that it already contains are not added again
+pub fn balance_in_medium(raw_eq: &RawEquation, medium: Medium, periodic_table: &PeriodicTable) -> Result<BalancedEquation, ChemError> {
+    let medium_species = return_on_error!(medium.species(periodic_table));
+    let medium_atoms: Vec<Atom> = medium_species.iter().flat_map(|molec| { molec.atoms.keys().cloned() }).collect();
+    let unconserved: Vec<UnconservedElement> = unconserved_elements(raw_eq).into_iter()
+        .filter(|elem| { !medium_atoms.contains(&elem.atom) })
+        .collect();
+    if !unconserved.is_empty() {
+        return Err(ChemError::UnconservedElements(unconserved));
+    }
+    let is_in_equation = |species: &Molecule| -> bool {
+        raw_eq.lhs.iter().chain(raw_eq.rhs.iter())
+            .any(|molec| { molec.atoms == species.atoms && molec.charge == species.charge })
+    };
+    let added_species: Vec<Molecule> = medium_species.into_iter().filter(|species| { !is_in_equation(species) }).collect();
+    balance_with_movable_species(raw_eq, &added_species)
+}
+
+/// Balances `raw_eq` completed with `movable_species`, which are added to the member where they are needed (or omitted
+/// if they are not needed), while the species of `raw_eq` stay in their member
+///
+/// Fails if there is not exactly one such balancing
+pub fn balance_with_movable_species(raw_eq: &RawEquation, movable_species: &[Molecule]) -> Result<BalancedEquation, ChemError> {
+    // the movable species are appended to the reactants, a negative coefficient moves them to the products
+    let mut extended_eq = raw_eq.clone();
+    extended_eq.lhs.extend(movable_species.iter().cloned());
+    let basis = matrix_for(&extended_eq).null_space_basis();
+    let vec = match basis.len() {
+        0 => return Err(ChemError::Inconsistent),
+        1 => &basis[0],
+        n_independent_reactions => return Err(ChemError::Underconstrained { n_independent_reactions })
+    };
+    let coefs = return_on_error!(to_i32_coefs(&to_primitive_integers(vec)));
+    let sign = if coefs[0] < 0 { -1 } else { 1 };
+    let signed_coefs: Vec<i32> = coefs.iter().map(|coef| { sign * coef }).collect();
+    // the coefficients of the movable species are between those of the reactants and those of the products
+    let mut original_coefs = signed_coefs[..raw_eq.lhs.len()].iter().chain(&signed_coefs[extended_eq.lhs.len()..]);
+    if original_coefs.any(|&coef| { coef <= 0 }) {
+        return Err(ChemError::Inconsistent);
+    }
+    Ok(balanced_equation_moving_negative_coefs(&extended_eq, &signed_coefs))
+}
+
+/// Quantities of the species of a reaction, computed from the quantities of its reactants
+#[derive(Debug, Clone)]
+pub struct ReactionQuantities {
+    /// the equation with the quantities of all the species, in mol
+    pub equation: QuantifiedEquation,
+    /// `None` when the quantities are computed from those of a single species, all the reactants being then consumed
+    pub limiting_reactant: Option<Molecule>,
+    /// the reactants other than the limiting reactant, in the order of the equation
+    pub excess_reactants: Vec<ExcessReactant>,
+}
+
+/// Reactant that is not entirely consumed by the reaction
+#[derive(Debug, Clone)]
+pub struct ExcessReactant {
+    pub molecule: Molecule,
+    /// in mol
+    pub consumed: ChemQuantity,
+    /// in mol
+    pub leftover: ChemQuantity,
+}
+
+/// returns the equation with all coefficients, the limiting reactant and the leftovers of the other reactants
+///
+/// The quantities are given either for all the reactants, or for a single species (e.g. the desired amount of a
+/// product), from which the others are deduced. The volumes of gases are converted at `conditions`
+pub fn compute_lhs_coefs(quant_eq: &QuantifiedEquation, conditions: &GasConditions) -> Result<ReactionQuantities, ChemError> {
     if quant_eq.only_reactants_quantities_known() {
-        let lhs_n_mol: Vec<f64> = quant_eq.lhs.iter().map(|(molec, quant_opt)| {
-            to_mol(molec, &quant_opt.clone().unwrap()).0
-        }).collect();
+        let lhs_n_mol: Vec<ChemQuantity> = return_on_error!(quant_eq.lhs.iter().map(|(molec, quant_opt)| {
+            convert_at(molec, &quant_opt.clone().unwrap(), ChemUnit::MOL, conditions)
+        }).collect());
         let pos_vect_res = solution_vec_for_balancing_of(&quant_eq.to_raw_eq());
         let (lhs_stoic_coefs, rhs_stoic_coefs) =
             match &pos_vect_res {
                 Ok(sol_vec) => sol_vec.split_at(lhs_n_mol.len()),
                 Err(err) => return Err(err.clone())
             };
-        let times_reaction: Vec<f64> = lhs_stoic_coefs.iter().zip(lhs_n_mol).map(|(st_coef, n_mol)| {
-            (n_mol as f64) / (*st_coef as f64)
+        // number of times the reaction can happen with each reactant, and its uncertainty
+        let times_reaction: Vec<(f64, f64)> = lhs_stoic_coefs.iter().zip(&lhs_n_mol).map(|(st_coef, n_mol)| {
+            (n_mol.0 / (*st_coef as f64), n_mol.2 / (*st_coef as f64))
         }).collect();
-        let times_reaction_per_reactant: Vec<(Molecule, f64)> = quant_eq.lhs.iter()
+        let times_reaction_per_reactant: Vec<(Molecule, (f64, f64))> = quant_eq.lhs.iter()
             .map(|(molec, _)| { molec.clone() }).zip(times_reaction)
             .collect();
-        let mut min: (Molecule, f64) = (Molecule { atoms: BTreeMap::new(), charge: 0, string_repr: None }, f64::MAX);
-        for (molec, times_reac) in times_reaction_per_reactant {
-            if times_reac < min.1 {
-                min = (molec, times_reac)
+        let mut min: (Molecule, (f64, f64)) = (Molecule { atoms: BTreeMap::new(), charge: 0, string_repr: None, phase: None }, (f64::MAX, 0.0));
+        let mut limiting_idx = 0;
+        for (idx, (molec, times_reac)) in times_reaction_per_reactant.into_iter().enumerate() {
+            if times_reac.0 < min.1.0 {
+                min = (molec, times_reac);
+                limiting_idx = idx;
             }
         }
+        // the other reactants are consumed as many times as the reaction happens with the limiting reactant
+        let excess_reactants: Vec<ExcessReactant> = quant_eq.lhs.iter().zip(lhs_stoic_coefs).zip(&lhs_n_mol).enumerate()
+            .filter(|(idx, _)| { *idx != limiting_idx })
+            .map(|(_, (((molec, _), &stoic_coef), n_mol))| {
+                let consumed = ChemQuantity((stoic_coef as f64) * min.1.0, ChemUnit::MOL, (stoic_coef as f64) * min.1.1);
+                let leftover = ChemQuantity((n_mol.0 - consumed.0).max(0.0), ChemUnit::MOL, n_mol.2.hypot(consumed.2));
+                ExcessReactant { molecule: molec.clone(), consumed, leftover }
+            })
+            .collect();
         let rhs_quantities: Vec<ChemQuantity> = rhs_stoic_coefs.iter()
-            .map(|&stoic_coef| { ChemQuantity((stoic_coef as f64) * min.1, Mol) })
+            .map(|&stoic_coef| { ChemQuantity((stoic_coef as f64) * min.1.0, ChemUnit::MOL, (stoic_coef as f64) * min.1.1) })
             .collect();
         let rhs: Vec<(Molecule, Option<ChemQuantity>)> = quant_eq.rhs.iter()
             .zip(rhs_quantities).map(|((m, _), quant)| { (m.clone(), Some(quant)) })
             .collect();
-        Ok((QuantifiedEquation {
-            rhs,
-            ..quant_eq.quantities_to_mol()
-        }, min.0))
+        Ok(ReactionQuantities {
+            equation: QuantifiedEquation {
+                rhs,
+                ..return_on_error!(quant_eq.quantities_to(ChemUnit::MOL, conditions))
+            },
+            limiting_reactant: Some(min.0),
+            excess_reactants,
+        })
+    } else if quant_eq.single_quantity_known() {
+        compute_coefs_from_single_quantity(quant_eq, conditions)
     } else {
-        Err(PositionedError(format!("quantities should be given for all reactants and for no product"), None))
+        Err(ChemError::QuantityLayout {
+            description: "quantities should be given for all reactants and for no product, or for a single species".to_string(),
+            span: None,
+        })
+    }
+}
+
+/// Yield of a product whose quantity has been observed at the end of the reaction
+#[derive(Debug, Clone)]
+pub struct ProductYield {
+    pub product: Molecule,
+    /// in mol
+    pub theoretical: ChemQuantity,
+    /// in mol
+    pub actual: ChemQuantity,
+    /// actual over theoretical yield, in %
+    pub percent_yield: f64,
+    pub percent_yield_uncertainty: f64,
+}
+
+impl ProductYield {
+    /// More product observed than the reaction can give, beyond the uncertainty, e.g. because the product is wet or impure
+    pub fn exceeds_theoretical(&self) -> bool {
+        self.percent_yield - self.percent_yield_uncertainty > 100.0
+    }
+}
+
+/// Quantities expected from the reaction, compared to the observed quantities of some of its products
+#[derive(Debug, Clone)]
+pub struct YieldReport {
+    /// theoretical quantities, as computed by `compute_lhs_coefs` without the observed quantities
+    pub quantities: ReactionQuantities,
+    /// the observed products, in the order of the equation
+    pub yields: Vec<ProductYield>,
+    /// fraction of the theoretical extent of the reaction implied by the observed products, in %
+    ///
+    /// The yield of a product is the extent of the reaction implied by its observed quantity (its quantity over its
+    /// coefficient) over the theoretical extent, as the theoretical quantities are all proportional to the same extent.
+    /// Each observed product thus estimates the same fraction, and the efficiency is the mean of these estimates
+    pub efficiency: f64,
+    pub efficiency_uncertainty: f64,
+}
+
+/// Computes the theoretical quantities of the reaction and the yields of the products at `observed_products`
+///
+/// The quantities of these products in `quant_eq` are the observed ones, the other quantities are used as in
+/// `compute_lhs_coefs`
+pub fn compute_yields(quant_eq: &QuantifiedEquation, observed_products: &[usize], conditions: &GasConditions) -> Result<YieldReport, ChemError> {
+    if observed_products.is_empty() || observed_products.iter().any(|&idx| { quant_eq.rhs.get(idx).is_none_or(|(_, qty_opt)| { qty_opt.is_none() }) }) {
+        return Err(ChemError::QuantityLayout {
+            description: "yields need at least one product with an observed quantity".to_string(),
+            span: None,
+        });
     }
+    let quantities = return_on_error!(compute_lhs_coefs(&quant_eq.without_products_quantities(observed_products), conditions));
+    let mut yields: Vec<ProductYield> = Vec::new();
+    for &idx in observed_products {
+        let (product, observed_qty) = &quant_eq.rhs[idx];
+        let actual = return_on_error!(convert_at(product, &observed_qty.clone().unwrap(), ChemUnit::MOL, conditions));
+        let theoretical = quantities.equation.rhs[idx].1.clone().unwrap();
+        if theoretical.0 <= 0.0 {
+            return Err(ChemError::QuantityLayout {
+                description: format!("the given quantities cannot give any {}, so its yield is undefined", product),
+                span: None,
+            });
+        }
+        let percent_yield = 100.0 * actual.0 / theoretical.0;
+        let percent_yield_uncertainty = 100.0 * product_uncertainty(
+            actual.0, actual.2, 1.0 / theoretical.0, theoretical.2 / theoretical.0.powi(2)
+        );
+        yields.push(ProductYield { product: product.clone(), theoretical, actual, percent_yield, percent_yield_uncertainty });
+    }
+    let n_yields = yields.len() as f64;
+    let efficiency = yields.iter().map(|y| { y.percent_yield }).sum::<f64>() / n_yields;
+    let efficiency_uncertainty = yields.iter().map(|y| { y.percent_yield_uncertainty.powi(2) }).sum::<f64>().sqrt() / n_yields;
+    Ok(YieldReport { quantities, yields, efficiency, efficiency_uncertainty })
+}
+
+/// Computes the quantities of all the species of `quant_eq` from the only species whose quantity is known
+fn compute_coefs_from_single_quantity(quant_eq: &QuantifiedEquation, conditions: &GasConditions) -> Result<ReactionQuantities, ChemError> {
+    let species: Vec<&(Molecule, Option<ChemQuantity>)> = quant_eq.lhs.iter().chain(&quant_eq.rhs).collect();
+    let known_idx = species.iter().position(|(_, qty_opt)| { qty_opt.is_some() }).unwrap();
+    let (known_molec, known_qty) = species[known_idx];
+    let known_n_mol = return_on_error!(convert_at(known_molec, &known_qty.clone().unwrap(), ChemUnit::MOL, conditions));
+    let stoic_coefs = return_on_error!(solution_vec_for_balancing_of(&quant_eq.to_raw_eq()));
+    // number of times the reaction happens, and its uncertainty
+    let known_coef = stoic_coefs[known_idx] as f64;
+    let times_reaction = (known_n_mol.0 / known_coef, known_n_mol.2 / known_coef);
+    let mut quantities: Vec<(Molecule, Option<ChemQuantity>)> = species.iter().zip(&stoic_coefs)
+        .map(|((molec, _), &stoic_coef)| {
+            (molec.clone(), Some(ChemQuantity((stoic_coef as f64) * times_reaction.0, ChemUnit::MOL, (stoic_coef as f64) * times_reaction.1)))
+        })
+        .collect();
+    let rhs = quantities.split_off(quant_eq.lhs.len());
+    Ok(ReactionQuantities {
+        equation: QuantifiedEquation { lhs: quantities, rhs, arrow: quant_eq.arrow.clone() },
+        limiting_reactant: None,
+        excess_reactants: Vec::new(),
+    })
 }
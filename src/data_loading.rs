@@ -1,28 +1,238 @@
-use crate::chemistry::{Atom, PeriodicTable};
-
-const CSV_SPLITTER: &str = ",";
-
-pub fn load_peridic_table_as_vec(file_content: &str) -> Vec<Atom> {
-    file_content.lines().skip(1).map(|line|{
-        let cols_to_match: Vec<&str> = line.split(CSV_SPLITTER).take(4).collect();
-        match cols_to_match[..] {
-            [ _atomic_num, name, code, atomic_mass_str ] => {
-                let atomic_mass: f64 = atomic_mass_str.parse().unwrap();
-                Atom {
-                    name: name.to_string(),
-                    code: code.to_string(),
-                    atomic_mass_milli_amu: (1000.0 * atomic_mass) as u64
-                }
-            }
-            _ => panic!("could not read recource file line")
-        }
-    }).collect()
-}
-
-pub fn load_periodic_table(file_content: &str) -> PeriodicTable {
-    load_peridic_table_as_vec(file_content)
-        .iter()
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use crate::chemistry::{Atom, NANO_AMU_PER_AMU, PeriodicTable, Phase};
+use crate::elements::ElementProperties;
+use crate::error::LoadError;
+use crate::return_on_error;
+
+const CSV_SPLITTER: char = ',';
+const CSV_QUOTE: char = '"';
+
+/// Columns without which an element cannot be loaded, the other columns of the file are optional
+const REQUIRED_COLUMNS: [&str; 4] = ["AtomicNumber", "Element", "Symbol", "AtomicMass"];
+
+pub fn load_peridic_table_as_vec(file_content: &str) -> Result<Vec<Atom>, LoadError> {
+    let elements = return_on_error!(load_elements_properties(file_content));
+    Ok(elements.into_iter().map(|props| { props.atom }).collect())
+}
+
+pub fn load_periodic_table(file_content: &str) -> Result<PeriodicTable, LoadError> {
+    let atoms = return_on_error!(load_peridic_table_as_vec(file_content));
+    Ok(atoms.iter()
         .map(|atom| { (atom.code.clone(), atom.clone()) })
-        .collect()
+        .collect())
+}
+
+/// Loads all the columns of the periodic table file, in the order of the file
+///
+/// Columns are identified by the names in the header line, so that they may appear in any order
+pub fn load_elements_properties(file_content: &str) -> Result<Vec<ElementProperties>, LoadError> {
+    let mut lines = file_content.lines().enumerate();
+    let header: Vec<String> = match lines.next() {
+        Some((_, header_line)) => return_on_error!(split_csv_line(header_line, 1)),
+        None => return Err(LoadError::EmptyFile)
+    };
+    for required_column in REQUIRED_COLUMNS {
+        if !header.iter().any(|col_name| { col_name == required_column }) {
+            return Err(LoadError::MissingColumn { column_name: required_column.to_string() });
+        }
+    }
+    let mut elements: Vec<ElementProperties> = Vec::new();
+    let mut symbols_lines: BTreeMap<String, usize> = BTreeMap::new();
+    let mut atomic_numbers_lines: BTreeMap<u32, usize> = BTreeMap::new();
+    for (line_idx, line) in lines {
+        let line_num = line_idx + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let cells = return_on_error!(split_csv_line(line, line_num));
+        if cells.len() != header.len() {
+            return Err(LoadError::ColumnCount { line: line_num, expected: header.len(), found: cells.len() });
+        }
+        let row = CsvRow { line: line_num, header: &header, cells };
+        let element = return_on_error!(element_properties_for(&row));
+        if let Some(&first_line) = symbols_lines.get(&element.atom.code) {
+            return Err(LoadError::DuplicateSymbol { line: line_num, symbol: element.atom.code, first_line });
+        }
+        if let Some(&first_line) = atomic_numbers_lines.get(&element.atomic_number) {
+            return Err(LoadError::DuplicateAtomicNumber { line: line_num, atomic_number: element.atomic_number, first_line });
+        }
+        symbols_lines.insert(element.atom.code.clone(), line_num);
+        atomic_numbers_lines.insert(element.atomic_number, line_num);
+        elements.push(element);
+    }
+    Ok(elements)
+}
+
+fn element_properties_for(row: &CsvRow) -> Result<ElementProperties, LoadError> {
+    let atomic_mass_str: String = return_on_error!(row.required("AtomicMass"));
+    let (atomic_mass_nano_amu, atomic_mass_uncertainty_nano_amu) = match uncertain_nano_amu_for(&atomic_mass_str) {
+        Some((mass, uncertainty)) if mass > 0 => (mass, uncertainty),
+        _ => return Err(row.invalid_cell_error("AtomicMass"))
+    };
+    let symbol: String = return_on_error!(row.required("Symbol"));
+    if !is_valid_symbol(&symbol) {
+        return Err(row.invalid_cell_error("Symbol"));
+    }
+    Ok(ElementProperties {
+        atom: Atom {
+            name: return_on_error!(row.required("Element")),
+            code: symbol,
+            atomic_mass_nano_amu,
+            atomic_mass_uncertainty_nano_amu,
+        },
+        atomic_number: return_on_error!(row.required("AtomicNumber")),
+        n_neutrons: return_on_error!(row.optional("NumberofNeutrons")),
+        n_protons: return_on_error!(row.optional("NumberofProtons")),
+        n_electrons: return_on_error!(row.optional("NumberofElectrons")),
+        period: return_on_error!(row.optional("Period")),
+        group: return_on_error!(row.optional("Group")),
+        phase: return_on_error!(row.phase("Phase")),
+        radioactive: return_on_error!(row.flag("Radioactive")),
+        natural: return_on_error!(row.flag("Natural")),
+        metal: return_on_error!(row.flag("Metal")),
+        nonmetal: return_on_error!(row.flag("Nonmetal")),
+        metalloid: return_on_error!(row.flag("Metalloid")),
+        element_type: return_on_error!(row.optional("Type")),
+        atomic_radius: return_on_error!(row.optional("AtomicRadius")),
+        electronegativity: return_on_error!(row.optional("Electronegativity")),
+        first_ionization: return_on_error!(row.optional("FirstIonization")),
+        density: return_on_error!(row.optional("Density")),
+        melting_point: return_on_error!(row.optional("MeltingPoint")),
+        boiling_point: return_on_error!(row.optional("BoilingPoint")),
+        n_isotopes: return_on_error!(row.optional("NumberOfIsotopes")),
+        discoverer: return_on_error!(row.optional("Discoverer")),
+        discovery_year: return_on_error!(row.optional("Year")),
+        specific_heat: return_on_error!(row.optional("SpecificHeat")),
+        n_shells: return_on_error!(row.optional("NumberofShells")),
+        n_valence_electrons: return_on_error!(row.optional("NumberofValence")),
+    })
+}
+
+/// Reads a mass and its uncertainty in u, given in one of the following notations:
+/// - concise notation, e.g. `1.00794(7)` for 1.00794 ± 0.00007
+/// - interval, e.g. `[10.806;10.821]` or `[10.806, 10.821]`, read as its middle ± its half-width
+/// - plain decimal, e.g. `1.00794`, read as exact
+fn uncertain_nano_amu_for(mass_str: &str) -> Option<(u64, u64)> {
+    if let Some(interval) = mass_str.strip_prefix('[').and_then(|rem| { rem.strip_suffix(']') }) {
+        let (lower_str, upper_str) = interval.split_once(';').or_else(|| { interval.split_once(',') })?;
+        let lower = nano_amu_for(lower_str.trim())?;
+        let upper = nano_amu_for(upper_str.trim())?;
+        if lower > upper {
+            return None;
+        }
+        Some((lower + (upper - lower) / 2, (upper - lower) / 2))
+    } else if let Some((value_str, uncertainty_str)) = mass_str.strip_suffix(')').and_then(|rem| { rem.split_once('(') }) {
+        let value = nano_amu_for(value_str)?;
+        // the uncertainty applies to the last digits of the value
+        let n_decimals = value_str.split_once('.').map_or(0, |(_, frac_part)| { frac_part.len() });
+        let uncertainty_digits: u64 = uncertainty_str.parse().ok()?;
+        let uncertainty = uncertainty_digits.checked_mul(10_u64.pow((NANO_AMU_PER_AMU.ilog10() as usize - n_decimals) as u32))?;
+        Some((value, uncertainty))
+    } else {
+        nano_amu_for(mass_str).map(|value| { (value, 0) })
+    }
+}
+
+/// Reads a decimal mass in u (e.g. `1.00794`) exactly, returns `None` if it is not a decimal with at most 9 decimals
+fn nano_amu_for(mass_str: &str) -> Option<u64> {
+    let (int_part, frac_part) = mass_str.split_once('.').unwrap_or((mass_str, ""));
+    let n_decimals = NANO_AMU_PER_AMU.ilog10() as usize;
+    let is_digits = |s: &str| { s.chars().all(|ch| { ch.is_ascii_digit() }) };
+    if int_part.is_empty() || frac_part.len() > n_decimals || !is_digits(int_part) || !is_digits(frac_part) {
+        return None;
+    }
+    let int_value: u64 = int_part.parse().ok()?;
+    let frac_value: u64 = format!("{:0<width$}", frac_part, width = n_decimals).parse().ok()?;
+    int_value.checked_mul(NANO_AMU_PER_AMU)?.checked_add(frac_value)
 }
 
+/// Symbols are an uppercase letter followed by lowercase letters, otherwise they could not be parsed in molecules
+fn is_valid_symbol(symbol: &str) -> bool {
+    let mut chars = symbol.chars();
+    chars.next().is_some_and(|head| { head.is_ascii_uppercase() }) && chars.all(|ch| { ch.is_ascii_lowercase() })
+}
+
+/// Cells of a line of the file, together with the column names
+struct CsvRow<'a> {
+    line: usize,
+    header: &'a Vec<String>,
+    cells: Vec<String>,
+}
+
+impl CsvRow<'_> {
+    fn column_idx(&self, column_name: &str) -> Option<usize> {
+        self.header.iter().position(|col_name| { col_name == column_name })
+    }
+
+    /// Must only be called with the name of an existing column
+    fn invalid_cell_error(&self, column_name: &str) -> LoadError {
+        let col_idx = self.column_idx(column_name).unwrap();
+        LoadError::InvalidCell {
+            line: self.line,
+            column: col_idx + 1,
+            column_name: column_name.to_string(),
+            cell: self.cells[col_idx].clone(),
+        }
+    }
+
+    /// Returns `None` if the column does not exist or the cell is empty, o.w. parses the cell (e.g. `8.99E-05` for a `f64`)
+    fn optional<T: FromStr>(&self, column_name: &str) -> Result<Option<T>, LoadError> {
+        match self.column_idx(column_name) {
+            Some(col_idx) if !self.cells[col_idx].is_empty() => match self.cells[col_idx].parse() {
+                Ok(value) => Ok(Some(value)),
+                Err(_) => Err(self.invalid_cell_error(column_name))
+            },
+            _ => Ok(None)
+        }
+    }
+
+    /// Must only be called on the `REQUIRED_COLUMNS`
+    fn required<T: FromStr>(&self, column_name: &str) -> Result<T, LoadError> {
+        match return_on_error!(self.optional(column_name)) {
+            Some(value) => Ok(value),
+            None => Err(self.invalid_cell_error(column_name))
+        }
+    }
+
+    /// Yes/no columns contain `yes` or nothing (or `no`)
+    fn flag(&self, column_name: &str) -> Result<bool, LoadError> {
+        match self.column_idx(column_name).map(|col_idx| { self.cells[col_idx].as_str() }) {
+            Some("yes") => Ok(true),
+            Some("") | Some("no") | None => Ok(false),
+            Some(_) => Err(self.invalid_cell_error(column_name))
+        }
+    }
+
+    fn phase(&self, column_name: &str) -> Result<Option<Phase>, LoadError> {
+        match self.column_idx(column_name).map(|col_idx| { self.cells[col_idx].as_str() }) {
+            Some("solid") => Ok(Some(Phase::Solid)),
+            Some("liq") | Some("liquid") => Ok(Some(Phase::Liquid)),
+            Some("gas") => Ok(Some(Phase::Gas)),
+            Some("") | Some("artificial") | None => Ok(None),
+            Some(_) => Err(self.invalid_cell_error(column_name))
+        }
+    }
+}
+
+/// Splits the line on commas, except inside double quotes (which are removed)
+fn split_csv_line(line: &str, line_num: usize) -> Result<Vec<String>, LoadError> {
+    let mut cells: Vec<String> = Vec::new();
+    let mut curr_cell = String::new();
+    let mut in_quotes = false;
+    for ch in line.chars() {
+        if ch == CSV_QUOTE {
+            in_quotes = !in_quotes;
+        } else if ch == CSV_SPLITTER && !in_quotes {
+            cells.push(curr_cell.trim().to_string());
+            curr_cell = String::new();
+        } else {
+            curr_cell.push(ch);
+        }
+    }
+    if in_quotes {
+        return Err(LoadError::UnclosedQuote { line: line_num, column: cells.len() + 1 });
+    }
+    cells.push(curr_cell.trim().to_string());
+    Ok(cells)
+}
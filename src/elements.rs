@@ -0,0 +1,276 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use crate::chemistry::{Atom, Phase};
+use crate::error::ChemError;
+use crate::return_on_error;
+
+/// All the data known about an element, as given by the columns of the periodic table file
+///
+/// Fields are `None` when the corresponding cell is empty
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElementProperties {
+    pub atom: Atom,
+    pub atomic_number: u32,
+    pub n_neutrons: Option<u32>,
+    pub n_protons: Option<u32>,
+    pub n_electrons: Option<u32>,
+    pub period: Option<u32>,
+    pub group: Option<u32>,
+    /// phase at standard conditions, `None` for artificial elements
+    pub phase: Option<Phase>,
+    pub radioactive: bool,
+    pub natural: bool,
+    pub metal: bool,
+    pub nonmetal: bool,
+    pub metalloid: bool,
+    /// family of the element, e.g. "Alkali Metal" or "Noble Gas"
+    pub element_type: Option<String>,
+    /// in angstroms
+    pub atomic_radius: Option<f64>,
+    /// on the Pauling scale
+    pub electronegativity: Option<f64>,
+    /// in eV
+    pub first_ionization: Option<f64>,
+    /// in g/cm³
+    pub density: Option<f64>,
+    /// in K
+    pub melting_point: Option<f64>,
+    /// in K
+    pub boiling_point: Option<f64>,
+    pub n_isotopes: Option<u32>,
+    pub discoverer: Option<String>,
+    pub discovery_year: Option<u32>,
+    /// in J/(g·K)
+    pub specific_heat: Option<f64>,
+    pub n_shells: Option<u32>,
+    pub n_valence_electrons: Option<u32>,
+}
+
+/// Names of the properties of `ElementProperties` (as used in filters) and their units, in display order
+pub const PROPERTIES: [(&str, &str); 29] = [
+    ("atomic_number", ""),
+    ("symbol", ""),
+    ("name", ""),
+    ("atomic_mass", "u"),
+    ("atomic_mass_uncertainty", "u"),
+    ("period", ""),
+    ("group", ""),
+    ("phase", ""),
+    ("type", ""),
+    ("electronegativity", ""),
+    ("atomic_radius", "Å"),
+    ("first_ionization", "eV"),
+    ("density", "g/cm³"),
+    ("melting_point", "K"),
+    ("boiling_point", "K"),
+    ("specific_heat", "J/(g·K)"),
+    ("n_protons", ""),
+    ("n_neutrons", ""),
+    ("n_electrons", ""),
+    ("n_shells", ""),
+    ("n_valence_electrons", ""),
+    ("n_isotopes", ""),
+    ("radioactive", ""),
+    ("natural", ""),
+    ("metal", ""),
+    ("nonmetal", ""),
+    ("metalloid", ""),
+    ("discoverer", ""),
+    ("year", ""),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyValue {
+    Number(f64),
+    Text(String),
+    Flag(bool),
+}
+
+impl PropertyValue {
+    /// Compares this value to `value_str`, interpreted according to the kind of this value
+    ///
+    /// Text is compared alphabetically, ignoring case. Returns `None` if `value_str` cannot be interpreted
+    fn compare_to(&self, value_str: &str) -> Option<Ordering> {
+        match self {
+            PropertyValue::Number(x) => value_str.parse::<f64>().ok().and_then(|y| { x.partial_cmp(&y) }),
+            PropertyValue::Text(text) => Some(text.to_lowercase().cmp(&value_str.to_lowercase())),
+            PropertyValue::Flag(flag) => match value_str.to_lowercase().as_str() {
+                "yes" | "true" => Some(flag.cmp(&true)),
+                "no" | "false" => Some(flag.cmp(&false)),
+                _ => None
+            }
+        }
+    }
+}
+
+impl Display for PropertyValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PropertyValue::Number(x) => write!(f, "{}", x),
+            PropertyValue::Text(text) => write!(f, "{}", text),
+            PropertyValue::Flag(flag) => write!(f, "{}", if *flag { "yes" } else { "no" })
+        }
+    }
+}
+
+impl ElementProperties {
+    /// Returns the value of the property with the given name (see `PROPERTIES`), or `None` if it is unknown
+    pub fn property(&self, name: &str) -> Option<PropertyValue> {
+        let number = |x: Option<u32>| { x.map(|x| { PropertyValue::Number(x as f64) }) };
+        let real = |x: Option<f64>| { x.map(PropertyValue::Number) };
+        let text = |x: &Option<String>| { x.clone().map(PropertyValue::Text) };
+        let flag = |x: bool| { Some(PropertyValue::Flag(x)) };
+        match name {
+            "atomic_number" => number(Some(self.atomic_number)),
+            "symbol" => text(&Some(self.atom.code.clone())),
+            "name" => text(&Some(self.atom.name.clone())),
+            "atomic_mass" => real(Some(self.atom.atomic_mass_amu())),
+            "atomic_mass_uncertainty" => real(Some(self.atom.atomic_mass_uncertainty_amu())),
+            "period" => number(self.period),
+            "group" => number(self.group),
+            "phase" => text(&self.phase.map(|phase| { phase.to_string() })),
+            "type" => text(&self.element_type),
+            "electronegativity" => real(self.electronegativity),
+            "atomic_radius" => real(self.atomic_radius),
+            "first_ionization" => real(self.first_ionization),
+            "density" => real(self.density),
+            "melting_point" => real(self.melting_point),
+            "boiling_point" => real(self.boiling_point),
+            "specific_heat" => real(self.specific_heat),
+            "n_protons" => number(self.n_protons),
+            "n_neutrons" => number(self.n_neutrons),
+            "n_electrons" => number(self.n_electrons),
+            "n_shells" => number(self.n_shells),
+            "n_valence_electrons" => number(self.n_valence_electrons),
+            "n_isotopes" => number(self.n_isotopes),
+            "radioactive" => flag(self.radioactive),
+            "natural" => flag(self.natural),
+            "metal" => flag(self.metal),
+            "nonmetal" => flag(self.nonmetal),
+            "metalloid" => flag(self.metalloid),
+            "discoverer" => text(&self.discoverer),
+            "year" => number(self.discovery_year),
+            _ => None
+        }
+    }
+}
+
+impl Display for ElementProperties {
+    /// One line per known property, e.g. `group: 17`
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (name, unit) in PROPERTIES {
+            if let Some(value) = self.property(name) {
+                if unit.is_empty() {
+                    return_on_error!(writeln!(f, "{}: {}", name, value));
+                } else {
+                    return_on_error!(writeln!(f, "{}: {} {}", name, value, unit));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the element with the given atomic number, symbol or name (symbols are matched case-sensitively first)
+pub fn find_element<'a>(elements: &'a [ElementProperties], key: &str) -> Option<&'a ElementProperties> {
+    if let Ok(atomic_number) = key.parse::<u32>() {
+        return elements.iter().find(|elem| { elem.atomic_number == atomic_number });
+    }
+    elements.iter().find(|elem| { elem.atom.code == key })
+        .or_else(|| { elements.iter().find(|elem| { elem.atom.name.eq_ignore_ascii_case(key) }) })
+        .or_else(|| { elements.iter().find(|elem| { elem.atom.code.eq_ignore_ascii_case(key) }) })
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    /// Supported operators, as written in the filters
+    const OPERATORS: [(&'static str, Comparison); 6] = [
+        ("!=", Comparison::NotEqual),
+        ("<=", Comparison::LessOrEqual),
+        (">=", Comparison::GreaterOrEqual),
+        ("=", Comparison::Equal),
+        ("<", Comparison::Less),
+        (">", Comparison::Greater),
+    ];
+
+    fn holds_for(&self, ordering: Ordering) -> bool {
+        match self {
+            Comparison::Equal => ordering == Ordering::Equal,
+            Comparison::NotEqual => ordering != Ordering::Equal,
+            Comparison::Less => ordering == Ordering::Less,
+            Comparison::LessOrEqual => ordering != Ordering::Greater,
+            Comparison::Greater => ordering == Ordering::Greater,
+            Comparison::GreaterOrEqual => ordering != Ordering::Less
+        }
+    }
+}
+
+/// Condition on a property of the elements, e.g. `electronegativity>3`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ElementFilter {
+    pub property: String,
+    pub comparison: Comparison,
+    pub value: String,
+}
+
+impl ElementFilter {
+    /// An element matches iff it has a value for the property and this value satisfies the comparison
+    pub fn matches(&self, element: &ElementProperties) -> bool {
+        element.property(&self.property)
+            .and_then(|value| { value.compare_to(&self.value) })
+            .is_some_and(|ordering| { self.comparison.holds_for(ordering) })
+    }
+}
+
+/// Parses a conjunction of filters separated by `and`, e.g. `group=17 and phase=gas`
+pub fn parse_element_filters(query: &str) -> Result<Vec<ElementFilter>, ChemError> {
+    let mut filters: Vec<ElementFilter> = Vec::new();
+    let mut offset: usize = 0;
+    for filter_str in query.split(FILTERS_SEPARATOR) {
+        filters.push(return_on_error!(parse_element_filter(filter_str, offset as u64)));
+        offset += filter_str.len() + FILTERS_SEPARATOR.len();
+    }
+    Ok(filters)
+}
+
+const FILTERS_SEPARATOR: &str = " and ";
+
+/// `offset` is the position of `filter_str` in the query, used for the spans of the errors
+fn parse_element_filter(filter_str: &str, offset: u64) -> Result<ElementFilter, ChemError> {
+    let start = offset + (filter_str.len() - filter_str.trim_start().len()) as u64;
+    let span = start..(start + filter_str.trim().len() as u64);
+    let operator = Comparison::OPERATORS.iter()
+        .filter_map(|(op_str, comparison)| { filter_str.find(op_str).map(|idx| { (idx, *op_str, *comparison) }) })
+        .min_by_key(|(idx, op_str, _)| { (*idx, -(op_str.len() as i64)) });
+    if let Some((op_idx, op_str, comparison)) = operator {
+        let property = filter_str[..op_idx].trim().to_lowercase();
+        let value = filter_str[(op_idx + op_str.len())..].trim().to_string();
+        if !PROPERTIES.iter().any(|(name, _)| { *name == property }) {
+            Err(ChemError::UnknownElementProperty {
+                span: start..(start + property.len() as u64),
+                property,
+            })
+        } else if value.is_empty() {
+            Err(ChemError::FilterFormat { filter: filter_str.trim().to_string(), span })
+        } else {
+            Ok(ElementFilter { property, comparison, value })
+        }
+    } else {
+        Err(ChemError::FilterFormat { filter: filter_str.trim().to_string(), span })
+    }
+}
+
+/// Returns the elements that match all the filters, in their original order
+pub fn filter_elements<'a>(elements: &'a [ElementProperties], filters: &[ElementFilter]) -> Vec<&'a ElementProperties> {
+    elements.iter().filter(|elem| { filters.iter().all(|filter| { filter.matches(elem) }) }).collect()
+}
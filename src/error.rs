@@ -0,0 +1,214 @@
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+use crate::chemistry::{ChemUnit, UnconservedElement};
+use crate::return_on_error;
+
+/// Range of positions (in characters) in the input string, end excluded
+pub type Span = Range<u64>;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ChemError {
+    /// a character sequence that does not belong to the input syntax
+    UnrecognizedToken { token: String, span: Span },
+    /// a valid token at a place where it is not allowed
+    UnexpectedToken { token: String, span: Span },
+    UnknownElement { symbol: String, span: Span },
+    UnknownUnit { unit: String, span: Span },
+    /// a quantity whose value is not a positive numeric literal, or that has no unit
+    QuantityFormat { quantity: String, span: Span },
+    /// a quantity of the species that cannot be converted to the expected unit, e.g. a volume to a mass
+    UnitConversion { from: ChemUnit, to: ChemUnit, species: String },
+    /// a concentration given for a quantity of solution of the wrong dimension, e.g. a molarity for a mass
+    SolutionQuantity { solution: String, concentration: String, span: Span },
+    /// a temperature or a pressure of gases that is malformed, in an unknown unit or given twice
+    ConditionsFormat { conditions: String, span: Span },
+    /// a purity that is not a percentage by mass, e.g. `@ 120%`
+    PurityFormat { purity: String, span: Span },
+    /// a parenthesis or bracket that is never closed, closed without being opened or closed by the other kind
+    UnbalancedParenthesis { description: String, span: Option<Span> },
+    /// a species that has no oxidation states as it contains no atom, e.g. `e^-`
+    SpeciesWithoutAtoms { species: String, span: Span },
+    /// a charge that does not match `^<charge><+/->` or `^<+/-><charge>`
+    ChargeFormat { span: Span },
+    /// an equation that does not consist of 2 members of the expected kind
+    EquationLayout { description: String, span: Option<Span> },
+    /// quantities given for a set of species from which the other quantities cannot be deduced
+    QuantityLayout { description: String, span: Option<Span> },
+    /// elements that appear in a single member of the equation
+    UnconservedElements(Vec<UnconservedElement>),
+    /// the equation has several independent balancings
+    Underconstrained { n_independent_reactions: usize },
+    /// the equation has no balancing
+    Inconsistent,
+    /// the equation has several independent balancings, but none of them has only positive coefficients (within the
+    /// explored range of coefficients, if `max_coefs_sum` is given)
+    NoPositiveBalancing { max_coefs_sum: Option<i64> },
+    /// the equation has positive balancings, but the search for the one with the smallest coefficients was too long
+    BalancingSearchLimit { max_combinations: u64 },
+    /// a balancing coefficient that exceeds the range of `i32`
+    CoefficientTooLarge { coefficient: String },
+    UnknownElementProperty { property: String, span: Span },
+    /// an element filter that does not match `<property><operator><value>`
+    FilterFormat { filter: String, span: Span },
+    UnknownCommand { command: String },
+    MissingArguments { command: String },
+    UnexpectedArguments { command: String },
+}
+
+impl ChemError {
+    /// Returns the span of the input that the error refers to, if any
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            ChemError::UnrecognizedToken { span, .. }
+            | ChemError::UnexpectedToken { span, .. }
+            | ChemError::UnknownElement { span, .. }
+            | ChemError::UnknownUnit { span, .. }
+            | ChemError::QuantityFormat { span, .. }
+            | ChemError::SolutionQuantity { span, .. }
+            | ChemError::ConditionsFormat { span, .. }
+            | ChemError::PurityFormat { span, .. }
+            | ChemError::SpeciesWithoutAtoms { span, .. }
+            | ChemError::ChargeFormat { span }
+            | ChemError::UnknownElementProperty { span, .. }
+            | ChemError::FilterFormat { span, .. } => Some(span.clone()),
+            ChemError::UnbalancedParenthesis { span, .. }
+            | ChemError::EquationLayout { span, .. }
+            | ChemError::QuantityLayout { span, .. } => span.clone(),
+            ChemError::UnconservedElements(elements) => elements.first().and_then(|elem| { elem.span() }),
+            _ => None
+        }
+    }
+
+    /// Returns the same error, with its span(s) moved `offset` positions to the right
+    pub fn shifted(self, offset: u64) -> ChemError {
+        let shift = |span: Span| -> Span { (span.start + offset)..(span.end + offset) };
+        match self {
+            ChemError::UnrecognizedToken { token, span } => ChemError::UnrecognizedToken { token, span: shift(span) },
+            ChemError::UnexpectedToken { token, span } => ChemError::UnexpectedToken { token, span: shift(span) },
+            ChemError::UnknownElement { symbol, span } => ChemError::UnknownElement { symbol, span: shift(span) },
+            ChemError::UnknownUnit { unit, span } => ChemError::UnknownUnit { unit, span: shift(span) },
+            ChemError::QuantityFormat { quantity, span } => ChemError::QuantityFormat { quantity, span: shift(span) },
+            ChemError::SolutionQuantity { solution, concentration, span } =>
+                ChemError::SolutionQuantity { solution, concentration, span: shift(span) },
+            ChemError::ConditionsFormat { conditions, span } => ChemError::ConditionsFormat { conditions, span: shift(span) },
+            ChemError::PurityFormat { purity, span } => ChemError::PurityFormat { purity, span: shift(span) },
+            ChemError::UnbalancedParenthesis { description, span } =>
+                ChemError::UnbalancedParenthesis { description, span: span.map(shift) },
+            ChemError::SpeciesWithoutAtoms { species, span } => ChemError::SpeciesWithoutAtoms { species, span: shift(span) },
+            ChemError::ChargeFormat { span } => ChemError::ChargeFormat { span: shift(span) },
+            ChemError::EquationLayout { description, span } =>
+                ChemError::EquationLayout { description, span: span.map(shift) },
+            ChemError::QuantityLayout { description, span } =>
+                ChemError::QuantityLayout { description, span: span.map(shift) },
+            ChemError::UnknownElementProperty { property, span } =>
+                ChemError::UnknownElementProperty { property, span: shift(span) },
+            ChemError::FilterFormat { filter, span } => ChemError::FilterFormat { filter, span: shift(span) },
+            ChemError::UnconservedElements(elements) => ChemError::UnconservedElements(
+                elements.into_iter().map(|elem| {
+                    UnconservedElement { position: elem.position.map(|pos| { pos + offset }), ..elem }
+                }).collect()
+            ),
+            other => other
+        }
+    }
+}
+
+impl Display for ChemError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ChemError::UnrecognizedToken { token, span } =>
+                write!(f, "unrecognized token: {} at position {}", token, span.start),
+            ChemError::UnexpectedToken { token, span } =>
+                write!(f, "unexpected: '{}' at position {}", token, span.start),
+            ChemError::UnknownElement { symbol, span } =>
+                write!(f, "unknown element: {} at position {}", symbol, span.start),
+            ChemError::UnknownUnit { unit, .. } => write!(f, "unknown unit: {}", unit),
+            ChemError::QuantityFormat { quantity, .. } => write!(f,
+                "malformed quantity: '{}', expected a positive number followed by a unit, e.g. '2.5e-3 mol', '.5 g' or '1_000 g'",
+                quantity
+            ),
+            ChemError::UnitConversion { from, to, species } => write!(f,
+                "cannot convert {} of {} ({}) to {} ({})", from, species, from.dimension(), to, to.dimension()
+            ),
+            ChemError::SolutionQuantity { solution, concentration, .. } => write!(f,
+                "a concentration in {} cannot apply to a quantity of solution in {}, molarities and concentrations in g/L \
+                apply to volumes and mass percents to masses", concentration, solution
+            ),
+            ChemError::ConditionsFormat { conditions, .. } => write!(f,
+                "malformed gas conditions: '{}', expected a temperature in K or °C and/or a pressure in Pa, kPa, bar, atm, \
+                mmHg or Torr, e.g. '298 K, 1 atm'", conditions
+            ),
+            ChemError::PurityFormat { purity, .. } => write!(f,
+                "malformed purity: '{}', expected '@' followed by a percentage by mass, greater than 0 and at most 100, \
+                e.g. '@ 92%'", purity
+            ),
+            ChemError::UnbalancedParenthesis { description, .. } => write!(f, "{}", description),
+            ChemError::SpeciesWithoutAtoms { species, .. } => write!(f, "{} contains no atom, it has no oxidation states", species),
+            ChemError::ChargeFormat { .. } =>
+                write!(f, "charge format error, expected '^<charge><+/->', e.g. '^3+', or '^<+/-><charge>', e.g. '^+3'"),
+            ChemError::EquationLayout { description, .. } => write!(f, "{}", description),
+            ChemError::QuantityLayout { description, .. } => write!(f, "{}", description),
+            ChemError::UnconservedElements(elements) => {
+                let descriptions: Vec<String> = elements.iter().map(|elem| { elem.to_string() }).collect();
+                write!(f, "elements not conserved: {}", descriptions.join(", "))
+            }
+            ChemError::Underconstrained { n_independent_reactions } => {
+                return_on_error!(write!(f, "solving failed, underconstrained equation"));
+                write!(f, " ({} independent reactions)", n_independent_reactions)
+            }
+            ChemError::Inconsistent => write!(f, "cannot balance equation"),
+            ChemError::NoPositiveBalancing { max_coefs_sum: Some(max_coefs_sum) } =>
+                write!(f, "no balancing with positive coefficients summing up to at most {}", max_coefs_sum),
+            ChemError::NoPositiveBalancing { max_coefs_sum: None } => write!(f, "no balancing with positive coefficients"),
+            ChemError::BalancingSearchLimit { max_combinations } =>
+                write!(f, "no minimal balancing found within {} combinations of the independent balancings", max_combinations),
+            ChemError::CoefficientTooLarge { coefficient } =>
+                write!(f, "cannot balance equation, coefficient {} is too large", coefficient),
+            ChemError::UnknownElementProperty { property, .. } => write!(f, "unknown element property: {}", property),
+            ChemError::FilterFormat { filter, .. } =>
+                write!(f, "filter format error in '{}', expected '<property><operator><value>', e.g. 'group=17'", filter),
+            ChemError::UnknownCommand { command } => write!(f, "unknown command: {}", command),
+            ChemError::MissingArguments { command } => write!(f, "{} needs argument(s)", command),
+            ChemError::UnexpectedArguments { command } => write!(f, "{} does not take arguments", command),
+        }
+    }
+}
+
+impl Error for ChemError {}
+
+/// Error in a periodic table file, lines and columns are numbered from 1
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum LoadError {
+    EmptyFile,
+    MissingColumn { column_name: String },
+    /// a line with a different number of cells than the header line
+    ColumnCount { line: usize, expected: usize, found: usize },
+    UnclosedQuote { line: usize, column: usize },
+    /// a cell that is empty although required, or cannot be read as a value of its column
+    InvalidCell { line: usize, column: usize, column_name: String, cell: String },
+    DuplicateSymbol { line: usize, symbol: String, first_line: usize },
+    DuplicateAtomicNumber { line: usize, atomic_number: u32, first_line: usize },
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::EmptyFile => write!(f, "empty file, expected a header line"),
+            LoadError::MissingColumn { column_name } => write!(f, "missing column: {}", column_name),
+            LoadError::ColumnCount { line, expected, found } =>
+                write!(f, "line {}: expected {} cells, found {}", line, expected, found),
+            LoadError::UnclosedQuote { line, column } => write!(f, "line {}, column {}: unclosed quote", line, column),
+            LoadError::InvalidCell { line, column, column_name, cell } =>
+                write!(f, "line {}, column {} ({}): invalid value '{}'", line, column, column_name, cell),
+            LoadError::DuplicateSymbol { line, symbol, first_line } =>
+                write!(f, "line {}: symbol {} already defined at line {}", line, symbol, first_line),
+            LoadError::DuplicateAtomicNumber { line, atomic_number, first_line } =>
+                write!(f, "line {}: atomic number {} already defined at line {}", line, atomic_number, first_line),
+        }
+    }
+}
+
+impl Error for LoadError {}
@@ -1,8 +1,11 @@
 extern crate core;
 
 pub mod chemistry;
+pub mod error;
 pub mod data_loading;
+pub mod elements;
 pub mod parsing;
+pub mod redox;
 pub mod util;
 pub mod lin_alg;
 pub mod arith;
@@ -1,11 +1,14 @@
 use std::cmp::min;
 use std::fmt::{Display, Formatter};
-use crate::arith::{gcd_vec, lcm};
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Signed, Zero};
+use crate::arith::to_primitive_integers;
 use crate::return_on_error;
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 // self.0 is the bi-dimensional row-major vector containing the coefficients
-pub struct Matrix(Vec<Vec<i32>>);
+pub struct Matrix(Vec<Vec<BigRational>>);
 
 impl Matrix {
     pub fn of_arr(arr: &[&[i32]]) -> Self {
@@ -18,19 +21,28 @@ impl Matrix {
     }
 
     pub fn of_row_major(coefs: &Vec<Vec<i32>>) -> Self {
+        let rational_coefs: Vec<Vec<BigRational>> = coefs.iter()
+            .map(|row| {
+                row.iter().map(|&coef| { BigRational::from_integer(BigInt::from(coef)) }).collect()
+            })
+            .collect();
+        Matrix::of_rationals(&rational_coefs)
+    }
+
+    pub fn of_rationals(coefs: &Vec<Vec<BigRational>>) -> Self {
         assert!(!coefs.is_empty());
-        let head_row: &Vec<i32> = &coefs[0];
+        let head_row: &Vec<BigRational> = &coefs[0];
         for row in coefs {
             assert_eq!(row.len(), head_row.len());
         }
         Matrix(coefs.clone())
     }
 
-    pub fn coef_at(&self, row: usize, col: usize) -> i32 {
-        self.0[row][col]
+    pub fn coef_at(&self, row: usize, col: usize) -> BigRational {
+        self.0[row][col].clone()
     }
 
-    pub fn coefs(&self) -> Vec<Vec<i32>> {
+    pub fn coefs(&self) -> Vec<Vec<BigRational>> {
         self.0.clone()
     }
 
@@ -43,43 +55,64 @@ impl Matrix {
     }
 
     /// Returns an equivalent diagonal matrix, or reports an error
+    ///
+    /// Each row of the returned matrix is scaled to coprime integers with a non-negative pivot
     pub fn diagonalized(&self) -> Result<Matrix, ()> {
         let mut coefs = self.coefs();
         let res = diagonalize(&mut coefs, self.n_rows(), self.n_cols());
-        if res { Ok(Matrix::of_row_major(&coefs)) } else { Err(()) }
+        if res { Ok(Matrix::of_rationals(&coefs)) } else { Err(()) }
     }
-    
-    fn is_row_full_zeros(row: &Vec<i32>) -> bool {
-        for &coef in row {
-            if coef != 0 {
+
+    fn is_row_full_zeros(row: &Vec<BigRational>) -> bool {
+        for coef in row {
+            if !coef.is_zero() {
                 return false
             }
         }
         true
     }
-    
+
     pub fn without_full_zero_rows(&self) -> Matrix {
         let mut coefs = self.coefs();
         coefs.retain(|row|{ !Self::is_row_full_zeros(row) });
-        Matrix::of_row_major(&coefs)
+        Matrix::of_rationals(&coefs)
     }
-    
-    pub fn diagonal(&self) -> Vec<i32> {
-        let mut diagonal: Vec<i32> = Vec::new();
+
+    pub fn diagonal(&self) -> Vec<BigRational> {
+        let mut diagonal: Vec<BigRational> = Vec::new();
         for i in 0..min(self.n_rows(), self.n_cols()){
             diagonal.push(self.coef_at(i, i));
         }
         diagonal
     }
-    
-    pub fn column(&self, col_idx: usize) -> Vec<i32> {
-        let mut col: Vec<i32> = Vec::new();
+
+    pub fn column(&self, col_idx: usize) -> Vec<BigRational> {
+        let mut col: Vec<BigRational> = Vec::new();
         for r in 0..self.n_rows() {
             col.push(self.coef_at(r, col_idx))
         }
         col
     }
-    
+
+    /// Returns a basis of the null space of the matrix, i.e. of the vectors `x` s.t. `self * x = 0`
+    ///
+    /// The basis contains one vector per column without pivot in the reduced row echelon form of the matrix
+    pub fn null_space_basis(&self) -> Vec<Vec<BigRational>> {
+        let mut coefs = self.coefs();
+        let n_cols = self.n_cols();
+        let pivot_cols = reduce_to_row_echelon_form(&mut coefs, self.n_rows(), n_cols);
+        let mut basis: Vec<Vec<BigRational>> = Vec::new();
+        for free_col in (0..n_cols).filter(|c| { !pivot_cols.contains(c) }) {
+            let mut vec: Vec<BigRational> = vec![BigRational::zero(); n_cols];
+            vec[free_col] = BigRational::one();
+            for (pivot_row, &pivot_col) in pivot_cols.iter().enumerate() {
+                vec[pivot_col] = -coefs[pivot_row][free_col].clone();
+            }
+            basis.push(vec);
+        }
+        basis
+    }
+
 }
 
 impl Display for Matrix {
@@ -87,7 +120,7 @@ impl Display for Matrix {
         let coefs = &self.0;
         for row in coefs {
             for coef in row {
-                return_on_error!(write!(f, "{:4} ", coef));
+                return_on_error!(write!(f, "{:>4} ", coef.to_string()));
             }
             return_on_error!(write!(f, "{}", "\n"));
         }
@@ -95,11 +128,67 @@ impl Display for Matrix {
     }
 }
 
+/// Returns `true` iff some linear combination of `vectors` (which must all have the same length) has only positive
+/// coefficients
+///
+/// As the combinations can be scaled, this is equivalent to the existence of weights `w` s.t. all the coefficients of
+/// the combination are at least 1, which is decided by eliminating the weights one by one (Fourier-Motzkin elimination)
+pub fn has_positive_combination(vectors: &[Vec<BigRational>]) -> bool {
+    if vectors.is_empty() {
+        return false;
+    }
+    let n_weights = vectors.len();
+    // each constraint `(a, b)` stands for `a . w >= b`, one per coefficient of the combination
+    let mut constraints: Vec<(Vec<BigRational>, BigRational)> = (0..vectors[0].len())
+        .map(|i| { (vectors.iter().map(|vec| { vec[i].clone() }).collect(), BigRational::one()) })
+        .collect();
+    for weight_idx in 0..n_weights {
+        let (lower_bounds, rest): (Vec<_>, Vec<_>) = constraints.into_iter()
+            .partition(|(a, _)| { a[weight_idx].is_positive() });
+        let (upper_bounds, mut next_constraints): (Vec<_>, Vec<_>) = rest.into_iter()
+            .partition(|(a, _)| { a[weight_idx].is_negative() });
+        // each pair of a lower and an upper bound on the weight gives a constraint without it
+        for (lower_a, lower_b) in &lower_bounds {
+            for (upper_a, upper_b) in &upper_bounds {
+                let lower_factor = -upper_a[weight_idx].clone();
+                let upper_factor = lower_a[weight_idx].clone();
+                let a: Vec<BigRational> = lower_a.iter().zip(upper_a)
+                    .map(|(lower_coef, upper_coef)| { lower_coef * &lower_factor + upper_coef * &upper_factor })
+                    .collect();
+                next_constraints.push((a, lower_b * &lower_factor + upper_b * &upper_factor));
+            }
+        }
+        constraints = without_redundant_constraints(next_constraints);
+    }
+    // no weight is left, so the constraints are `0 >= b`
+    constraints.iter().all(|(_, b)| { !b.is_positive() })
+}
+
+/// Scales each constraint `a . w >= b` so that the first non-zero coefficient of `a` is 1 or -1, then removes the
+/// duplicates, as well as the constraints that always hold
+fn without_redundant_constraints(constraints: Vec<(Vec<BigRational>, BigRational)>) -> Vec<(Vec<BigRational>, BigRational)> {
+    let mut simplified: Vec<(Vec<BigRational>, BigRational)> = Vec::new();
+    for (a, b) in constraints {
+        let (a, b) = match a.iter().find(|coef| { !coef.is_zero() }) {
+            Some(first_non_zero) => {
+                let scale = first_non_zero.abs();
+                (a.iter().map(|coef| { coef / &scale }).collect(), b / scale)
+            }
+            None if !b.is_positive() => continue,
+            None => (a, b)
+        };
+        if !simplified.contains(&(a.clone(), b.clone())) {
+            simplified.push((a, b));
+        }
+    }
+    simplified
+}
+
 #[allow(dead_code)]
-fn print_matrix_like(coefs: &Vec<Vec<i32>>) {
+fn print_matrix_like(coefs: &Vec<Vec<BigRational>>) {
     for row in coefs {
         for coef in row {
-            print!("{:4} ", coef);
+            print!("{:>4} ", coef.to_string());
         }
         println!();
     }
@@ -116,14 +205,14 @@ macro_rules! return_if_false {
 }
 
 /// Searches the first row s.t. its coef. at index `pivot_idx` is non zero
-/// 
+///
 /// If found, swaps this row with the one at index `pivot_idx`
-/// 
+///
 /// Returns `true` if such a row was found or if all the rows starting at `pivot_idx` are 0s, o.w.
 /// returns `false` (to report that diagonalizing is impossible)
-fn place_first_non_zero_at(coefs: &mut Vec<Vec<i32>>, pivot_idx: usize, n_rows: usize, n_cols: usize) -> bool {
+fn place_first_non_zero_at(coefs: &mut Vec<Vec<BigRational>>, pivot_idx: usize, n_rows: usize, n_cols: usize) -> bool {
     for r in pivot_idx..n_rows {
-        if coefs[r][pivot_idx] != 0 {
+        if !coefs[r][pivot_idx].is_zero() {
             if r > pivot_idx {
                 coefs.swap(r, pivot_idx);
             }
@@ -133,7 +222,7 @@ fn place_first_non_zero_at(coefs: &mut Vec<Vec<i32>>, pivot_idx: usize, n_rows:
     // if rows from the pivot row are all 0s then it is ok
     for r in pivot_idx..n_rows {
         for c in 0..n_cols {
-            if coefs[r][c] != 0 {
+            if !coefs[r][c].is_zero() {
                 return false
             }
         }
@@ -141,42 +230,65 @@ fn place_first_non_zero_at(coefs: &mut Vec<Vec<i32>>, pivot_idx: usize, n_rows:
     true
 }
 
-/// Divides all coefficients in the row by their GCD
-fn simplify_row_if_possible(row: &mut Vec<i32>) {
-    let gcd = gcd_vec(row);
-    if gcd != 0 {
-        for i in 0..row.len() {
-            row[i] /= gcd;
-        }
+/// Replaces the coefficients in the row by the coprime integers with the same ratios
+fn simplify_row(row: &mut Vec<BigRational>) {
+    let simplified = to_primitive_integers(row);
+    for (coef, simplified_coef) in row.iter_mut().zip(simplified) {
+        *coef = BigRational::from_integer(simplified_coef);
     }
 }
 
 /// Uses operations on the rows to zero out `coef[row_to_zero_out_idx][pivot_idx]`
-fn zero_out_row_at_col(coefs: &mut Vec<Vec<i32>>, row_to_zero_out_idx: usize, pivot_idx: usize) {
-    let pivot = coefs[pivot_idx][pivot_idx];
-    if pivot != 0 && coefs[row_to_zero_out_idx][pivot_idx] != 0 {
-        let lcm = lcm(coefs[row_to_zero_out_idx][pivot_idx], pivot);
-        let row_mul = lcm / coefs[row_to_zero_out_idx][pivot_idx];
-        let pivot_row_mul = lcm / pivot;
+fn zero_out_row_at_col(coefs: &mut Vec<Vec<BigRational>>, row_to_zero_out_idx: usize, pivot_idx: usize) {
+    zero_out_row_using_pivot(coefs, row_to_zero_out_idx, pivot_idx, pivot_idx)
+}
+
+/// Subtracts a multiple of row `pivot_row_idx` from row `row_to_zero_out_idx` to zero out `coef[row_to_zero_out_idx][pivot_col_idx]`
+fn zero_out_row_using_pivot(coefs: &mut Vec<Vec<BigRational>>, row_to_zero_out_idx: usize, pivot_row_idx: usize, pivot_col_idx: usize) {
+    let pivot = coefs[pivot_row_idx][pivot_col_idx].clone();
+    if !pivot.is_zero() && !coefs[row_to_zero_out_idx][pivot_col_idx].is_zero() {
+        let factor = &coefs[row_to_zero_out_idx][pivot_col_idx] / &pivot;
         for c in 0..coefs[row_to_zero_out_idx].len() {
-            coefs[row_to_zero_out_idx][c] =
-                row_mul * coefs[row_to_zero_out_idx][c] - pivot_row_mul * coefs[pivot_idx][c];
+            let delta = &factor * &coefs[pivot_row_idx][c];
+            coefs[row_to_zero_out_idx][c] -= delta;
         }
-        simplify_row_if_possible(&mut coefs[row_to_zero_out_idx])
     }
 }
 
+/// Transforms `coefs` into its reduced row echelon form (all pivots are 1 and are the only non-zero coefs. in their column)
+///
+/// Unlike `diagonalize`, columns without a pivot are skipped, so that this never fails
+///
+/// Returns the indices of the columns containing a pivot, in the order of the rows that contain them
+fn reduce_to_row_echelon_form(coefs: &mut Vec<Vec<BigRational>>, n_rows: usize, n_cols: usize) -> Vec<usize> {
+    let mut pivot_cols: Vec<usize> = Vec::new();
+    for col in 0..n_cols {
+        let pivot_row_idx = pivot_cols.len();
+        if pivot_row_idx == n_rows {
+            break
+        }
+        if let Some(r) = (pivot_row_idx..n_rows).find(|&r| { !coefs[r][col].is_zero() }) {
+            coefs.swap(r, pivot_row_idx);
+            let pivot = coefs[pivot_row_idx][col].clone();
+            for coef in coefs[pivot_row_idx].iter_mut() {
+                *coef /= &pivot;
+            }
+            for r in (0..n_rows).filter(|&r| { r != pivot_row_idx }) {
+                zero_out_row_using_pivot(coefs, r, pivot_row_idx, col);
+            }
+            pivot_cols.push(col);
+        }
+    }
+    pivot_cols
+}
+
 /// Uses Gaussian elimination to transform `coefs` into an equivalent diagonal matrix
-/// 
+///
 /// Returns `true` iff it succeeds
-fn diagonalize(coefs: &mut Vec<Vec<i32>>, n_rows: usize, n_cols: usize) -> bool {
+fn diagonalize(coefs: &mut Vec<Vec<BigRational>>, n_rows: usize, n_cols: usize) -> bool {
     let diag_len: usize = min(n_rows, n_cols);
 
-    for i in 0..coefs.len() {
-        simplify_row_if_possible(&mut coefs[i]);
-    }
-
-    let zero_out_bottom_left = |coefs: &mut Vec<Vec<i32>>| {
+    let zero_out_bottom_left = |coefs: &mut Vec<Vec<BigRational>>| {
         for pivot_idx in 0..diag_len {
             return_if_false!(place_first_non_zero_at(coefs, pivot_idx, n_rows, n_cols));
             for r in (pivot_idx + 1)..n_rows {
@@ -186,7 +298,7 @@ fn diagonalize(coefs: &mut Vec<Vec<i32>>, n_rows: usize, n_cols: usize) -> bool
         true
     };
 
-    let zero_out_top_right = |coefs: &mut Vec<Vec<i32>>| {
+    let zero_out_top_right = |coefs: &mut Vec<Vec<BigRational>>| {
         for pivot_idx in (0..diag_len).rev() {
             for r in 0..pivot_idx {
                 zero_out_row_at_col(coefs, r, pivot_idx);
@@ -194,11 +306,11 @@ fn diagonalize(coefs: &mut Vec<Vec<i32>>, n_rows: usize, n_cols: usize) -> bool
         }
     };
 
-    let make_pivots_non_negative = |coefs: &mut Vec<Vec<i32>>|{
+    let make_pivots_non_negative = |coefs: &mut Vec<Vec<BigRational>>|{
         for pivot_idx in 0..diag_len {
-            if coefs[pivot_idx][pivot_idx] < 0 {
+            if coefs[pivot_idx][pivot_idx].is_negative() {
                 for i in 0..n_cols {
-                    coefs[pivot_idx][i] *= -1;
+                    coefs[pivot_idx][i] = -coefs[pivot_idx][i].clone();
                 }
             }
         }
@@ -206,7 +318,9 @@ fn diagonalize(coefs: &mut Vec<Vec<i32>>, n_rows: usize, n_cols: usize) -> bool
 
     return_if_false!(zero_out_bottom_left(coefs));
     zero_out_top_right(coefs);
+    for row in coefs.iter_mut() {
+        simplify_row(row);
+    }
     make_pivots_non_negative(coefs);
     true
 }
-
@@ -1,37 +1,91 @@
 use std::cmp::max;
 use std::collections::btree_map::BTreeMap;
 use std::env::args;
+use std::fs;
 use std::io::{BufRead, Write};
 use std::process::exit;
 
-use Stoichio_calc::chemistry::{balance, compute_lhs_coefs, PeriodicTable};
-use Stoichio_calc::data_loading::load_periodic_table;
-use Stoichio_calc::parsing::{parse_molecule, parse_quantified_equation, parse_raw_equation, PositionedError, tokenize};
+use Stoichio_calc::chemistry::{balance, balance_all, balance_in_medium, balance_with, BalancingCriterion, ChemQuantity, ChemUnit, compute_lhs_coefs, compute_yields, convert, convert_at, format_with_uncertainty, GasConditions, gross_quantity, Medium, Molecule, PeriodicTable, Phase, products_concentrations, QuantifiedEquation, RawEquation, ReactionQuantities, YieldReport};
+use Stoichio_calc::data_loading::load_elements_properties;
+use Stoichio_calc::elements::{ElementProperties, filter_elements, find_element, parse_element_filters};
+use Stoichio_calc::error::ChemError;
+use Stoichio_calc::redox::{format_oxidation_state, half_reactions, oxidation_states, redox_report};
+use Stoichio_calc::parsing::{check_element_conservation, EquationAnnotations, locate_unconserved_elements, parse_annotated_equation, parse_molecule, parse_raw_equation, split_reaction_conditions, tokenize};
 use Stoichio_calc::return_on_error;
 
-type ArgsCommand = fn(&str, &Context) -> Result<(), PositionedError>;
-type NoArgsCommand = fn(&Context) -> Result<(), PositionedError>;
+type ArgsCommand = fn(&str, &Context) -> Result<(), ChemError>;
+type NoArgsCommand = fn(&Context) -> Result<(), ChemError>;
 
 struct Context<'a> {
     periodic_table: PeriodicTable,
+    elements: Vec<ElementProperties>,
     args_cmds: BTreeMap<&'a str, (&'a ArgsCommand, &'a str)>,
     no_args_cmds: BTreeMap<&'a str, (&'a NoArgsCommand, &'a str)>,
 }
 
+const PERIODIC_TABLE_OPTION: &str = "--periodic-table";
+
 fn main() {
 
-    // include resource file in .exe
-    let periodic_table_file_content = include_str!("../res/periodic_table.csv");
+    let mut args: Vec<String> = args().skip(1).collect();
+
+    // include default resource file in .exe
+    let mut periodic_table_file_content = include_str!("../res/periodic_table.csv").to_string();
+    if args.first().is_some_and(|arg| { arg == PERIODIC_TABLE_OPTION }) {
+        if args.len() < 2 {
+            println!("{} needs a path to a csv file", PERIODIC_TABLE_OPTION);
+            exit(1)
+        }
+        let path = args[1].clone();
+        args.drain(0..2);
+        match fs::read_to_string(&path) {
+            Ok(file_content) => periodic_table_file_content = file_content,
+            Err(err) => {
+                println!("could not read {}: {}", path, err);
+                exit(1)
+            }
+        }
+    }
+    let elements = match load_elements_properties(&periodic_table_file_content) {
+        Ok(elements) => elements,
+        Err(err) => {
+            println!("could not load periodic table: {}", err);
+            exit(1)
+        }
+    };
 
     let ctx = Context {
-        periodic_table: load_periodic_table(periodic_table_file_content),
+        periodic_table: elements.iter().map(|elem| { (elem.atom.code.clone(), elem.atom.clone()) }).collect(),
+        elements,
         args_cmds: BTreeMap::from([
             ("mass", (&(compute_mass_cmd as ArgsCommand),
                       "mass <molecule> - display the atomic mass of the molecule in atomic mass unit")),
             ("balance", (&(balance_equation_cmd as ArgsCommand),
-                         "balance <equation> - balance the equation, e.g. 'balance H2 + O2 => H2O'")),
+                         "balance [--all|--minimal|--acidic|--basic] <equation> - balance the equation, e.g. 'balance H2 + O2 => H2O', \
+                         with --all list the independent reactions of an ambiguous equation, \
+                         with --minimal pick its balancing with the smallest positive coefficients, \
+                         with --acidic or --basic add H^+ or OH^- and H2O where a redox reaction needs them")),
             ("compute", (&(compute_products_cmd as ArgsCommand),
-            "compute <equation> - compute the amounts of products, e.g. 'compute 1 mol H2 + 0.5 g O2 => H2O'"))
+            "compute <equation> [at <temperature>, <pressure>] [in <volume>] - compute the amounts of products, \
+            e.g. 'compute 1 mol H2 + 0.5 g O2 => H2O', or of all species from a single one, e.g. 'compute H2 + O2 => 10 g H2O', \
+            quantities may be given as solutions, e.g. '25 mL 0.1 M HCl' or '50 g 10 % NaCl', or as volumes of gases, \
+            e.g. '22.4 L O2(g)', with 'at <temperature>, <pressure>' take the volumes of gases at these conditions \
+            instead of 0 °C, 1 atm, with 'in <volume>' display the concentrations of the products in this volume of solution, \
+            products followed by 'observed', e.g. '4.1 g H2O observed', get their theoretical and percent yields, \
+            species followed by '@ <percent>%', e.g. '100 g CaCO3 @ 92%', are impure, with this purity by mass")),
+            ("oxstates", (&(oxidation_states_cmd as ArgsCommand),
+                          "oxstates <molecule> - display the oxidation state of each element of the molecule, e.g. 'oxstates Fe3O4'")),
+            ("halves", (&(half_reactions_cmd as ArgsCommand),
+                        "halves [--acidic|--basic] <equation> - split the redox equation into balanced half-reactions, \
+                        e.g. 'halves MnO4^- + Fe^2+ + H^+ => Mn^2+ + Fe^3+ + H2O'")),
+            ("redox", (&(redox_cmd as ArgsCommand),
+                       "redox <equation> - balance the equation and identify the oxidized and reduced elements, \
+                       e.g. 'redox Fe^2+ + Cr => Fe + Cr^3+'")),
+            ("element", (&(element_cmd as ArgsCommand),
+                         "element <symbol|name|number> - display the data known about the element, e.g. 'element Fe'")),
+            ("elements", (&(elements_cmd as ArgsCommand),
+                          "elements where <filter> [and <filter>]* - list the elements matching the filters, \
+                          e.g. 'elements where group=17 and electronegativity>3'"))
         ]),
         no_args_cmds: BTreeMap::from([
             ("exit", (&(exit_cmd as NoArgsCommand), "exit - exit the program")),
@@ -39,7 +93,6 @@ fn main() {
         ]),
     };
 
-    let args: Vec<String> = args().skip(1).collect();
     if args.is_empty() { run_cli(&ctx); }
     else { execute_cmd(&ctx, &args.join("")); }
 
@@ -62,53 +115,42 @@ fn execute_cmd(ctx: &Context, raw_line: &String) {
     let sp: Vec<&str> = trimmed_line
         .splitn(2, ' ')
         .collect();
-    let result: Result<(), PositionedError> = match sp[..] {
+    let result: Result<(), ChemError> = match sp[..] {
         [] => Ok(()), // empty line, do nothing
         [cmd] => call_no_arg_command(cmd, &ctx),
         [cmd, args] => call_arg_command(&cmd.to_lowercase(), args, &ctx),
         _ => panic!("should not happen")
     };
-    if let Err(PositionedError(msg, pos_opt)) = result {
-        println!("an error occured: {}", msg);
-        if let Some(pos) = pos_opt {
-            let padding_len = sp[0].len() + max(1 + pos, 0) as usize;
+    if let Err(err) = result {
+        println!("an error occured: {}", err);
+        if let Some(span) = err.span() {
+            let padding_len = sp[0].len() + 1 + span.start as usize;
             let padding = str::repeat(" ", padding_len);
+            let carets = str::repeat("^", max(span.end - span.start, 1) as usize);
             println!("{}", trimmed_line);
-            println!("{}^", padding)
+            println!("{}{}", padding, carets)
         }
     }
     println!();
 }
 
-fn call_no_arg_command(cmd: &str, ctx: &Context) -> Result<(), PositionedError> {
+fn call_no_arg_command(cmd: &str, ctx: &Context) -> Result<(), ChemError> {
     if let Some((cmd_fn, _)) = ctx.no_args_cmds.get(cmd) {
         cmd_fn(&ctx)
     } else if ctx.args_cmds.contains_key(cmd) {
-        Err(PositionedError(
-            format!("{} needs argument(s)", cmd),
-            None,
-        ))
+        Err(ChemError::MissingArguments { command: cmd.to_string() })
     } else {
-        Err(PositionedError(
-            format!("unknown command: {}", cmd),
-            None,
-        ))
+        Err(ChemError::UnknownCommand { command: cmd.to_string() })
     }
 }
 
-fn call_arg_command(cmd: &str, args: &str, ctx: &Context) -> Result<(), PositionedError> {
+fn call_arg_command(cmd: &str, args: &str, ctx: &Context) -> Result<(), ChemError> {
     if let Some((cmd_fn, _)) = ctx.args_cmds.get(cmd) {
         cmd_fn(args, &ctx)
     } else if ctx.no_args_cmds.contains_key(cmd) {
-        Err(PositionedError(
-            format!("{} does not take arguments", cmd),
-            None,
-        ))
+        Err(ChemError::UnexpectedArguments { command: cmd.to_string() })
     } else {
-        Err(PositionedError(
-            format!("unknown command: {}", cmd),
-            None,
-        ))
+        Err(ChemError::UnknownCommand { command: cmd.to_string() })
     }
 }
 
@@ -127,45 +169,325 @@ fn display_help(ctx: &Context) {
     println!();
 }
 
-fn compute_mass_cmd(args: &str, ctx: &Context) -> Result<(), PositionedError> {
+fn compute_mass_cmd(args: &str, ctx: &Context) -> Result<(), ChemError> {
     match parse_molecule(&ctx.periodic_table, &tokenize(&args.to_string())) {
         Ok(molecule) => {
-            Ok(println!("molecular mass: {} u", molecule.mass_amu()))
+            let uncertainty = molecule.mass_uncertainty_amu();
+            if uncertainty > 0.0 {
+                Ok(println!("molecular mass: {} u", format_with_uncertainty(molecule.mass_amu(), uncertainty)))
+            } else {
+                Ok(println!("molecular mass: {} u", molecule.mass_amu()))
+            }
         }
         Err(msg) => Err(msg)
     }
 }
 
-fn balance_equation_cmd(args: &str, ctx: &Context) -> Result<(), PositionedError> {
-    match parse_raw_equation(&ctx.periodic_table, &tokenize(&args.to_string())){
-        Ok(raw_equation) => {
-            match balance(&raw_equation){
-                Ok(balanced_equation) => {
-                    println!("{}", balanced_equation);
-                    Ok(())
-                }
-                Err(pos_err) => Err(pos_err)
-            }
+fn oxidation_states_cmd(args: &str, ctx: &Context) -> Result<(), ChemError> {
+    let molecule = return_on_error!(parse_molecule(&ctx.periodic_table, &tokenize(&args.to_string())));
+    if molecule.atoms.is_empty() {
+        return Err(ChemError::SpeciesWithoutAtoms { species: molecule.to_string(), span: 0..(args.chars().count() as u64) });
+    }
+    let states: Vec<String> = oxidation_states(&molecule, &ctx.elements).iter()
+        .map(|(atom, state)| { format!("{}: {}", atom.code, format_oxidation_state(state)) })
+        .collect();
+    println!("{}", states.join(", "));
+    Ok(())
+}
+
+fn redox_cmd(args: &str, ctx: &Context) -> Result<(), ChemError> {
+    let raw_equation = return_on_error!(parse_checked_raw_equation(args, ctx));
+    let balanced_equation = return_on_error!(balance(&raw_equation));
+    println!("{}", balanced_equation);
+    let report = redox_report(&balanced_equation, &ctx.elements);
+    if !report.is_redox() {
+        println!("no element changes oxidation state");
+        return Ok(());
+    }
+    for change in &report.changes {
+        println!("{}", change);
+    }
+    let format_agents = |agents: &Vec<Molecule>| -> String {
+        agents.iter().map(|molec| { molec.to_string() }).collect::<Vec<String>>().join(", ")
+    };
+    println!("oxidizing agent(s): {}", format_agents(&report.oxidizing_agents));
+    println!("reducing agent(s): {}", format_agents(&report.reducing_agents));
+    println!("electrons transferred: {}", report.n_electrons);
+    Ok(())
+}
+
+fn half_reactions_cmd(args: &str, ctx: &Context) -> Result<(), ChemError> {
+    let (eq_args, medium, offset) = if let Some((eq_args, offset)) = strip_option(args, "--acidic") {
+        (eq_args, Some(Medium::Acidic), offset)
+    } else if let Some((eq_args, offset)) = strip_option(args, "--basic") {
+        (eq_args, Some(Medium::Basic), offset)
+    } else {
+        (args, None, 0)
+    };
+    let tokens = tokenize(&eq_args.to_string());
+    let raw_equation = return_on_error!(parse_raw_equation(&ctx.periodic_table, &tokens).map_err(|err| { err.shifted(offset) }));
+    let half_reactions = return_on_error!(half_reactions(&raw_equation, medium, &ctx.periodic_table, &ctx.elements));
+    println!("{}", half_reactions);
+    Ok(())
+}
+
+fn balance_equation_cmd(args: &str, ctx: &Context) -> Result<(), ChemError> {
+    if let Some((eq_args, offset)) = strip_option(args, "--all") {
+        return shift_error(balance_all_cmd(eq_args, ctx), offset);
+    }
+    if let Some((eq_args, offset)) = strip_option(args, "--minimal") {
+        return shift_error(balance_minimal_cmd(eq_args, ctx), offset);
+    }
+    if let Some((eq_args, offset)) = strip_option(args, "--acidic") {
+        return shift_error(balance_in_medium_cmd(eq_args, Medium::Acidic, ctx), offset);
+    }
+    if let Some((eq_args, offset)) = strip_option(args, "--basic") {
+        return shift_error(balance_in_medium_cmd(eq_args, Medium::Basic, ctx), offset);
+    }
+    let raw_equation = return_on_error!(parse_checked_raw_equation(args, ctx));
+    match balance(&raw_equation){
+        Ok(balanced_equation) => {
+            println!("{}", balanced_equation);
+            Ok(())
         }
         Err(pos_err) => Err(pos_err)
     }
 }
 
-fn compute_products_cmd(args: &str, ctx: &Context) -> Result<(), PositionedError> {
-    let (eq, limiting_reactant) = return_on_error!(compute_lhs_coefs(
-        &return_on_error!(parse_quantified_equation(&ctx.periodic_table, &tokenize(&args.to_string())))
-    ));
-    println!("{}", eq);
-    println!("{}", eq.quantities_to_grams());
-    println!("limiting reactant: {}", limiting_reactant);
+fn balance_all_cmd(args: &str, ctx: &Context) -> Result<(), ChemError> {
+    let raw_equation = return_on_error!(parse_checked_raw_equation(args, ctx));
+    let equations = return_on_error!(balance_all(&raw_equation));
+    if equations.len() == 1 {
+        println!("unique balancing:");
+    } else {
+        println!("{} independent reactions, any balancing is a combination of them:", equations.len());
+    }
+    for equation in equations {
+        println!("{}", equation);
+    }
+    Ok(())
+}
+
+fn balance_minimal_cmd(args: &str, ctx: &Context) -> Result<(), ChemError> {
+    let raw_equation = return_on_error!(parse_checked_raw_equation(args, ctx));
+    let balancing = return_on_error!(balance_with(&raw_equation, BalancingCriterion::MinimalCoefficientsSum));
+    println!("{}", balancing.equation);
+    if balancing.chosen_among_several {
+        println!("chosen among several balancings: positive coefficients with the smallest sum");
+    }
+    Ok(())
+}
+
+/// Elements other than hydrogen and oxygen must be conserved, the species of the medium are added by the balancing
+fn balance_in_medium_cmd(args: &str, medium: Medium, ctx: &Context) -> Result<(), ChemError> {
+    let tokens = tokenize(&args.to_string());
+    let raw_equation = return_on_error!(parse_raw_equation(&ctx.periodic_table, &tokens));
+    match balance_in_medium(&raw_equation, medium, &ctx.periodic_table) {
+        Ok(balanced_equation) => {
+            println!("{}", balanced_equation);
+            Ok(())
+        }
+        Err(ChemError::UnconservedElements(unconserved)) =>
+            Err(ChemError::UnconservedElements(locate_unconserved_elements(&tokens, &unconserved))),
+        Err(err) => Err(err)
+    }
+}
+
+/// Parses the equation and checks that its elements are conserved, so that errors can point to the faulty element
+fn parse_checked_raw_equation(args: &str, ctx: &Context) -> Result<RawEquation, ChemError> {
+    let tokens = tokenize(&args.to_string());
+    let raw_equation = return_on_error!(parse_raw_equation(&ctx.periodic_table, &tokens));
+    return_on_error!(check_element_conservation(&raw_equation, &tokens));
+    Ok(raw_equation)
+}
+
+/// If `args` starts with `option`, returns the remaining arguments and their offset in `args`
+fn strip_option<'a>(args: &'a str, option: &str) -> Option<(&'a str, u64)> {
+    let rem = args.strip_prefix(option)?;
+    if rem.is_empty() || rem.starts_with(' ') {
+        Some((rem, option.len() as u64))
+    } else {
+        None
+    }
+}
+
+/// Shifts the position of the error (if any) by `offset`
+fn shift_error(res: Result<(), ChemError>, offset: u64) -> Result<(), ChemError> {
+    res.map_err(|err| { err.shifted(offset) })
+}
+
+fn compute_products_cmd(args: &str, ctx: &Context) -> Result<(), ChemError> {
+    let (eq_args, reaction_conditions) = return_on_error!(split_reaction_conditions(args));
+    let conditions = reaction_conditions.gases;
+    let tokens = tokenize(&eq_args.to_string());
+    let (observed_equation, annotations) = return_on_error!(parse_annotated_equation(&ctx.periodic_table, &tokens));
+    return_on_error!(check_element_conservation(&observed_equation.to_raw_eq(), &tokens));
+    // the observed quantities are compared to the computed ones, they are not inputs of the computation
+    let quantified_equation = observed_equation.without_products_quantities(&annotations.observed_products);
+    let yield_report = if annotations.observed_products.is_empty() {
+        None
+    } else {
+        Some(return_on_error!(compute_yields(&observed_equation, &annotations.observed_products, &conditions)))
+    };
+    let quantities = match &yield_report {
+        Some(report) => report.quantities.clone(),
+        None => return_on_error!(compute_lhs_coefs(&quantified_equation, &conditions))
+    };
+    println!("{}", quantities.equation);
+    let grams_eq = return_on_error!(grams_equation(&quantified_equation, &quantities.equation, &conditions));
+    println!("{}", grams_eq);
+    print_gross_amounts(&grams_eq, &annotations);
+    return_on_error!(print_excess_reactants(&quantities));
+    return_on_error!(print_gas_volumes(&quantities.equation, &conditions));
+    if let Some(volume) = reaction_conditions.final_volume {
+        return_on_error!(print_concentrations(&quantities.equation, &volume));
+    }
+    if let Some(report) = yield_report {
+        return_on_error!(print_yields(&report, &observed_equation, &annotations.observed_products, &conditions));
+    }
+    Ok(())
+}
+
+/// The quantities of `computed_eq` in grams, except for those given in `given_eq`, which are converted from their given
+/// quantities, to avoid a round trip through mol adding uncertainty
+fn grams_equation(given_eq: &QuantifiedEquation, computed_eq: &QuantifiedEquation, conditions: &GasConditions) -> Result<QuantifiedEquation, ChemError> {
+    let prefer_given = |given: &[(Molecule, Option<ChemQuantity>)], computed: &[(Molecule, Option<ChemQuantity>)]| {
+        given.iter().zip(computed)
+            .map(|((molec, given_qty), (_, computed_qty))| {
+                let qty = given_qty.as_ref().or(computed_qty.as_ref()).unwrap();
+                grams_for(molec, qty, conditions).map(|grams| { (molec.clone(), Some(grams)) })
+            })
+            .collect::<Result<Vec<(Molecule, Option<ChemQuantity>)>, ChemError>>()
+    };
+    Ok(QuantifiedEquation {
+        lhs: return_on_error!(prefer_given(&given_eq.lhs, &computed_eq.lhs)),
+        rhs: return_on_error!(prefer_given(&given_eq.rhs, &computed_eq.rhs)),
+        arrow: computed_eq.arrow.clone(),
+    })
+}
+
+/// The quantities of `grams_eq` are those of the pure species, impure species are weighed with their impurities
+fn print_gross_amounts(grams_eq: &QuantifiedEquation, annotations: &EquationAnnotations) {
+    let gross_amounts: Vec<String> = grams_eq.lhs.iter().zip(&annotations.lhs_purities)
+        .chain(grams_eq.rhs.iter().zip(&annotations.rhs_purities))
+        .filter_map(|((molec, pure_opt), purity_opt)| {
+            let (pure, purity) = (pure_opt.as_ref()?, (*purity_opt)?);
+            let gross = gross_quantity(pure, purity)?;
+            Some(format!("{} @ {} %: {} ({} pure)", molec, format_with_uncertainty(purity * 100.0, 0.0), gross, pure))
+        })
+        .collect();
+    if !gross_amounts.is_empty() {
+        println!("gross amounts: {}", gross_amounts.join("; "));
+    }
+}
+
+/// Prints nothing when the quantities are deduced from a single species, as there is no limiting reactant
+fn print_excess_reactants(quantities: &ReactionQuantities) -> Result<(), ChemError> {
+    if let Some(limiting_reactant) = &quantities.limiting_reactant {
+        let excess_reactants: Vec<String> = return_on_error!(quantities.excess_reactants.iter()
+            .map(|excess| {
+                if excess.molecule.is_electron() {
+                    return Ok(format!("{}: {} consumed, {} left over", excess.molecule, excess.consumed, excess.leftover));
+                }
+                let consumed_grams = return_on_error!(convert(&excess.molecule, &excess.consumed, ChemUnit::GRAM));
+                let leftover_grams = return_on_error!(convert(&excess.molecule, &excess.leftover, ChemUnit::GRAM));
+                Ok(format!("{}: {} ({}) consumed, {} ({}) left over",
+                           excess.molecule, excess.consumed, consumed_grams, excess.leftover, leftover_grams))
+            })
+            .collect::<Result<Vec<String>, ChemError>>());
+        println!("excess reactants: {}", if excess_reactants.is_empty() { "none".to_string() } else { excess_reactants.join("; ") });
+        println!("limiting reactant: {}", limiting_reactant);
+    }
+    Ok(())
+}
+
+/// Prints the volumes of the products annotated as gases, if any
+fn print_gas_volumes(mol_eq: &QuantifiedEquation, conditions: &GasConditions) -> Result<(), ChemError> {
+    let gas_volumes: Vec<String> = return_on_error!(mol_eq.rhs.iter()
+        .filter(|(molec, _)| { molec.phase == Some(Phase::Gas) })
+        .map(|(molec, qty)| {
+            convert_at(molec, &qty.clone().unwrap(), ChemUnit::LITER, conditions).map(|volume| { format!("{} {}", volume, molec) })
+        })
+        .collect::<Result<Vec<String>, ChemError>>());
+    if !gas_volumes.is_empty() {
+        println!("gas volumes at {}: {}", conditions, gas_volumes.join(", "));
+    }
+    Ok(())
+}
+
+/// Prints the concentrations of the dissolved products, see `products_concentrations`
+fn print_concentrations(mol_eq: &QuantifiedEquation, volume: &ChemQuantity) -> Result<(), ChemError> {
+    let concentrations: Vec<String> = return_on_error!(products_concentrations(mol_eq, volume)).iter()
+        .map(|(molec, conc)| { format!("{} {}", conc, molec) })
+        .collect();
+    println!("concentrations in {}: {}", volume, if concentrations.is_empty() { "none".to_string() } else { concentrations.join(", ") });
+    Ok(())
+}
+
+/// `observed_equation` - the equation with the observed quantities, at the indices `observed_products`
+fn print_yields(report: &YieldReport, observed_equation: &QuantifiedEquation, observed_products: &[usize], conditions: &GasConditions) -> Result<(), ChemError> {
+    for (&idx, product_yield) in observed_products.iter().zip(&report.yields) {
+        let observed_qty = observed_equation.rhs[idx].1.clone().unwrap();
+        let actual_grams = return_on_error!(grams_for(&product_yield.product, &observed_qty, conditions));
+        let theoretical_grams = return_on_error!(grams_for(&product_yield.product, &product_yield.theoretical, conditions));
+        let warning = if product_yield.exceeds_theoretical() { " (above 100 %, the product may be wet or impure)" } else { "" };
+        println!("yield of {}: {} observed, {} theoretical, {} %{}", product_yield.product, actual_grams, theoretical_grams,
+                 format_with_uncertainty(product_yield.percent_yield, product_yield.percent_yield_uncertainty), warning);
+    }
+    println!("efficiency: {} % of the theoretical extent of the reaction",
+             format_with_uncertainty(report.efficiency, report.efficiency_uncertainty));
     Ok(())
 }
 
-fn exit_cmd(_ctx: &Context) -> Result<(), PositionedError> {
+/// Converts a quantity of `molec` to grams for display, electrons having no mass are kept in their unit
+fn grams_for(molec: &Molecule, qty: &ChemQuantity, conditions: &GasConditions) -> Result<ChemQuantity, ChemError> {
+    if molec.is_electron() {
+        Ok(qty.clone())
+    } else {
+        convert_at(molec, qty, ChemUnit::GRAM, conditions)
+    }
+}
+
+fn element_cmd(args: &str, ctx: &Context) -> Result<(), ChemError> {
+    let key = args.trim();
+    match find_element(&ctx.elements, key) {
+        Some(element) => {
+            print!("{}", element);
+            Ok(())
+        }
+        None => {
+            let start = (args.len() - args.trim_start().len()) as u64;
+            Err(ChemError::UnknownElement { symbol: key.to_string(), span: start..(start + key.len() as u64) })
+        }
+    }
+}
+
+fn elements_cmd(args: &str, ctx: &Context) -> Result<(), ChemError> {
+    if let Some((query, offset)) = strip_option(args, "where") {
+        let filters = return_on_error!(parse_element_filters(query).map_err(|err| { err.shifted(offset) }));
+        let matching = filter_elements(&ctx.elements, &filters);
+        for element in &matching {
+            let values: Vec<String> = filters.iter()
+                .map(|filter| {
+                    let value = element.property(&filter.property).map_or(String::new(), |v| { v.to_string() });
+                    format!("{}: {}", filter.property, value)
+                })
+                .collect();
+            println!("{:>3}  {:<3} {:<14} {}", element.atomic_number, element.atom.code, element.atom.name, values.join(", "));
+        }
+        println!("{} matching element(s)", matching.len());
+        Ok(())
+    } else {
+        let token = args.split(' ').next().unwrap_or("");
+        Err(ChemError::UnexpectedToken { token: token.to_string(), span: 0..(token.len() as u64) })
+    }
+}
+
+fn exit_cmd(_ctx: &Context) -> Result<(), ChemError> {
     exit(0)
 }
 
-fn help_cmd(ctx: &Context) -> Result<(), PositionedError> {
+fn help_cmd(ctx: &Context) -> Result<(), ChemError> {
     display_help(ctx);
     Ok(())
 }
@@ -1,13 +1,21 @@
 use std::collections::btree_map::BTreeMap;
 use std::fmt::{Debug, Display, Formatter};
 
-use TokenType::{Alphabetic, ClosingParenthesis, ClosingBracket, Arrow, NoType, Numeric, OpeningParenthesis, OpeningBracket, Whitespace};
+use TokenType::{Alphabetic, ClosingParenthesis, ClosingBracket, Arrow, NoType, Numeric, OpeningParenthesis, OpeningBracket, UnitSymbol, Whitespace};
 
-use crate::chemistry::{Atom, chem_unit_for, ChemQuantity, Molecule, PeriodicTable, QuantifiedEquation, RawEquation};
-use crate::parsing::TokenType::{Dot, Exponent, Minus, Plus};
+use crate::chemistry::{Atom, chem_unit_for, ChemQuantity, concentration_for, Dimension, EquationSide, GasConditions, Molecule, PeriodicTable, Phase, pressure_for, pure_quantity, QuantifiedEquation, RawEquation, temperature_for, unconserved_elements, UnconservedElement};
+use crate::error::{ChemError, Span};
+use crate::parsing::TokenType::{Adduct, At, Dot, Exponent, Minus, Plus};
 use crate::return_on_error;
 
 const ARROW_PARTS: [char; 3] = ['=', '<', '>'];
+const ADDUCT_SEPARATORS: [char; 2] = ['·', '*'];
+const ELECTRON_SYMBOL: &str = "e";
+const UNIT_SYMBOLS: [char; 2] = ['/', '%'];
+/// Keyword that follows the quantity of a product measured at the end of a reaction, e.g. `4.1 g H2O observed`
+pub const OBSERVED_KEYWORD: &str = "observed";
+/// Introduces the purity of a species, as a percentage by mass, e.g. `100 g CaCO3 @ 92%`
+const PURITY_SIGN: char = '@';
 
 #[derive(Eq, PartialEq, Debug, Copy, Clone)]
 pub enum TokenType {
@@ -21,7 +29,13 @@ pub enum TokenType {
     Plus,
     Minus,
     Exponent,
+    /// separator between the parts of an adduct, e.g. `·` in `CuSO4·5H2O` (a `Dot` may also be used)
+    Adduct,
     Arrow,
+    /// character that only appears in units, e.g. `/` in `g/L` or `%`
+    UnitSymbol,
+    /// `PURITY_SIGN`, before the purity of a species
+    At,
     Whitespace,
     NoType,
 }
@@ -36,17 +50,10 @@ impl Display for Token {
     }
 }
 
-#[derive(Debug, Eq, PartialEq, Clone)]
-/// PositionedError(error message, optional position)
-pub struct PositionedError(pub String, pub Option<u64>);
-
-impl Display for PositionedError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        if let Some(pos) = self.1 {
-            write!(f, "{}: {}", pos, self.0)
-        } else {
-            write!(f, "?: {}", self.0)
-        }
+impl Token {
+    /// Returns the positions covered by the token in the input string
+    pub fn span(&self) -> Span {
+        self.2..(self.2 + self.0.chars().count() as u64)
     }
 }
 
@@ -63,20 +70,92 @@ fn token_type_for(c: &char) -> (TokenType, bool) {
         '+' => (Plus, true),
         '-' => (Minus, true),
         '^' => (Exponent, true),
+        _ if ADDUCT_SEPARATORS.contains(c) => (Adduct, true),
         _ if ARROW_PARTS.contains(c) => (Arrow, false),
+        _ if UNIT_SYMBOLS.contains(c) => (UnitSymbol, true),
+        _ if *c == PURITY_SIGN => (At, true),
         c if c.is_ascii_whitespace() => (Whitespace, false),
         _ => (NoType, false)
     }
 }
 
+/// Returns the number of characters of the numeric literal that starts at `start` in `chars` (0 if there is none)
+///
+/// Literals may start with a minus sign or a dot, and contain digits, dots, underscores and an exponent (e.g. `e-3`).
+/// The characters are only grouped here, the literal is validated by `parse_numeric_literal`
+fn numeric_literal_len(chars: &[char], start: usize) -> usize {
+    let is_digit_at = |idx: usize| { chars.get(idx).is_some_and(|c| { c.is_ascii_digit() }) };
+    let is_char_at = |idx: usize, expected: &[char]| { chars.get(idx).is_some_and(|c| { expected.contains(c) }) };
+    let mut idx = start;
+    if is_char_at(idx, &['-']) {
+        idx += 1;
+    }
+    let starts_with_digit = is_digit_at(idx) || (is_char_at(idx, &['.']) && is_digit_at(idx + 1));
+    if !starts_with_digit {
+        return 0;
+    }
+    while idx < chars.len() {
+        if is_digit_at(idx) || is_char_at(idx, &['.', '_']) {
+            idx += 1;
+        } else if is_char_at(idx, &['e', 'E']) && is_digit_at(idx + 1) {
+            idx += 2;
+        } else if is_char_at(idx, &['e', 'E']) && is_char_at(idx + 1, &['+', '-']) && is_digit_at(idx + 2) {
+            idx += 3;
+        } else {
+            break;
+        }
+    }
+    idx - start
+}
+
+/// Reads a positive numeric literal, e.g. `2`, `0.5`, `.5`, `2.5e-3` or `1_000` (underscores must be between digits)
+pub fn parse_numeric_literal(literal: &str) -> Option<f64> {
+    let chars: Vec<char> = literal.chars().collect();
+    let underscores_between_digits = chars.iter().enumerate()
+        .filter(|(_, c)| { **c == '_' })
+        .all(|(idx, _)| { idx > 0 && chars[idx - 1].is_ascii_digit() && chars.get(idx + 1).is_some_and(|c| { c.is_ascii_digit() }) });
+    let cleaned: String = chars.iter().filter(|c| { **c != '_' }).collect();
+    let (mantissa, exponent) = cleaned.split_once(['e', 'E']).unwrap_or((&cleaned, "0"));
+    let is_digits = |s: &str| { s.chars().all(|c| { c.is_ascii_digit() }) };
+    let (int_part, frac_part) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let exponent_digits = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+    if !underscores_between_digits || int_part.len() + frac_part.len() == 0 || !is_digits(int_part) || !is_digits(frac_part)
+        || exponent_digits.is_empty() || !is_digits(exponent_digits) {
+        return None;
+    }
+    cleaned.parse().ok().filter(|value: &f64| { value.is_finite() })
+}
+
 pub fn tokenize(txt: &String) -> Vec<Token> {
     let mut acc_token_str = String::new();
     let mut acc_tok_type = NoType;
     let mut acc_tok_start: u64 = 0;
     let mut tokens: Vec<Token> = Vec::new();
 
+    let chars: Vec<char> = txt.chars().collect();
     let mut pos: u64 = 0;
-    for c in txt.chars() {
+    let mut literal_end: usize = 0;
+    for (idx, &c) in chars.iter().enumerate() {
+        if idx < literal_end {
+            pos += 1;
+            continue;
+        }
+        // a number at the beginning of a word is a quantity, as a whole numeric literal (e.g. `2.5e-3` in `2.5e-3 mol`)
+        let is_word_start = acc_token_str.is_empty() || acc_tok_type == Whitespace || acc_tok_type == Arrow || acc_tok_type == At;
+        let literal_len = if is_word_start { numeric_literal_len(&chars, idx) } else { 0 };
+        if literal_len > 0 {
+            if !acc_token_str.is_empty() {
+                tokens.push(Token(acc_token_str.clone(), acc_tok_type, acc_tok_start));
+            }
+            let literal: String = chars[idx..(idx + literal_len)].iter().collect();
+            tokens.push(Token(literal, Numeric, pos));
+            acc_token_str.clear();
+            acc_tok_type = NoType;
+            acc_tok_start = pos + literal_len as u64;
+            literal_end = idx + literal_len;
+            pos += 1;
+            continue;
+        }
         let (c_tok_type, force_start) = token_type_for(&c);
         if c_tok_type == acc_tok_type && !force_start {
             acc_token_str.push(c);
@@ -91,14 +170,16 @@ pub fn tokenize(txt: &String) -> Vec<Token> {
         }
         pos += 1;
     }
-    tokens.push(Token(acc_token_str.clone(), acc_tok_type, acc_tok_start));
+    if !acc_token_str.is_empty() || tokens.is_empty() {
+        tokens.push(Token(acc_token_str.clone(), acc_tok_type, acc_tok_start));
+    }
     tokens
 }
 
 /// Check parentheses and makes sure that no `NoType` token is in the `tokens` vector
 ///
 /// Returns `Err(message)` if a violation is found, `Ok(())` o.w.
-fn check_token_seq(tokens: &Vec<Token>) -> Result<(), PositionedError> {
+fn check_token_seq(tokens: &Vec<Token>) -> Result<(), ChemError> {
     let mut parentheses: Vec<Token> = Vec::new();
     for tok in tokens {
         match tok {
@@ -108,14 +189,14 @@ fn check_token_seq(tokens: &Vec<Token>) -> Result<(), PositionedError> {
                 match parentheses.pop() {
                     Some(Token(_, OpeningParenthesis, _)) => {}
                     Some(Token(_, OpeningBracket, opening_pos)) =>
-                        return Err(PositionedError(
-                            format!("'[' at position {} closed by ')' at position {}", opening_pos, closing_pos),
-                            Some(*closing_pos),
-                        )),
-                    None => return Err(PositionedError(
-                        "')' closed but never opened".to_string(),
-                        Some(*closing_pos),
-                    )),
+                        return Err(ChemError::UnbalancedParenthesis {
+                            description: format!("'[' at position {} closed by ')' at position {}", opening_pos, closing_pos),
+                            span: Some(tok.span()),
+                        }),
+                    None => return Err(ChemError::UnbalancedParenthesis {
+                        description: "')' closed but never opened".to_string(),
+                        span: Some(tok.span()),
+                    }),
                     _ => panic!("should not happen")
                 }
             }
@@ -123,35 +204,35 @@ fn check_token_seq(tokens: &Vec<Token>) -> Result<(), PositionedError> {
                 match parentheses.pop() {
                     Some(Token(_, OpeningBracket, _)) => {}
                     Some(Token(_, OpeningParenthesis, opening_pos)) =>
-                        return Err(PositionedError(
-                            format!("'(' at position {} closed by ']' at position {}", opening_pos, closing_pos),
-                            Some(*closing_pos),
-                        )),
-                    None => return Err(PositionedError(
-                        "']' closed but never opened".to_string(),
-                        Some(*closing_pos),
-                    )),
+                        return Err(ChemError::UnbalancedParenthesis {
+                            description: format!("'(' at position {} closed by ']' at position {}", opening_pos, closing_pos),
+                            span: Some(tok.span()),
+                        }),
+                    None => return Err(ChemError::UnbalancedParenthesis {
+                        description: "']' closed but never opened".to_string(),
+                        span: Some(tok.span()),
+                    }),
                     _ => panic!("should not happen")
                 }
             }
-            Token(txt, NoType, pos) => return Err(PositionedError(
-                format!("unrecognized token: {} at position {}", txt, pos),
-                Some(*pos),
-            )),
+            Token(txt, NoType, _) => return Err(ChemError::UnrecognizedToken {
+                token: txt.clone(),
+                span: tok.span(),
+            }),
             _ => {}
         }
     }
     match parentheses.pop() {
-        Some(Token(_, OpeningBracket, pos)) =>
-            Err(PositionedError(
-                format!("'[' at position {} never closed", pos),
-                Some(pos),
-            )),
-        Some(Token(_, OpeningParenthesis, pos)) =>
-            Err(PositionedError(
-                format!("'(' at position {} never closed", pos),
-                Some(pos),
-            )),
+        Some(opening @ Token(_, OpeningBracket, _)) =>
+            Err(ChemError::UnbalancedParenthesis {
+                description: format!("'[' at position {} never closed", opening.2),
+                span: Some(opening.span()),
+            }),
+        Some(opening @ Token(_, OpeningParenthesis, _)) =>
+            Err(ChemError::UnbalancedParenthesis {
+                description: format!("'(' at position {} never closed", opening.2),
+                span: Some(opening.span()),
+            }),
         Some(_) => panic!("should not happen"),
         None => Ok(())
     }
@@ -167,8 +248,8 @@ fn merge_atom_into_seq(atoms_seq: &mut BTreeMap<Atom, u32>, atom: Atom, coef: u3
 
 /// Parses an atom sequence expected to correspond to a molecule (or a part of it)
 ///
-/// Returns a map atom -> coef and a charge (in a pair), or a `PositionedError` if an error occurs
-fn parse_atoms_seq(atoms: &PeriodicTable, tokens: &Vec<Token>) -> Result<(BTreeMap<Atom, u32>, i32), PositionedError> {
+/// Returns a map atom -> coef and a charge (in a pair), or a `ChemError` if an error occurs
+fn parse_atoms_seq(atoms: &PeriodicTable, tokens: &Vec<Token>) -> Result<(BTreeMap<Atom, u32>, i32), ChemError> {
     let mut rem_tokens = tokens.clone();
     let mut atoms_seq: BTreeMap<Atom, u32> = BTreeMap::new();
     let mut charge = 0;
@@ -179,6 +260,7 @@ fn parse_atoms_seq(atoms: &PeriodicTable, tokens: &Vec<Token>) -> Result<(BTreeM
 
             // alphabetic token -> atom, possibly followed by a coefficient
             Token(alpha, Alphabetic, pos) => {
+                let span = pos..(pos + alpha.chars().count() as u64);
                 if let Some(atom) = atoms.get(&alpha) {
                     let curr_coef: u32 = if let Some(Token(num, Numeric, _)) = rem_tokens.get(0) {
                         let parsed_coef = num.parse().unwrap();
@@ -187,15 +269,12 @@ fn parse_atoms_seq(atoms: &PeriodicTable, tokens: &Vec<Token>) -> Result<(BTreeM
                     } else { 1 };
                     merge_atom_into_seq(&mut atoms_seq, atom.clone(), curr_coef);
                 } else {
-                    return Err(PositionedError(
-                        format!("unknown element: {} at position {}", alpha, pos),
-                        Some(pos),
-                    ));
+                    return Err(ChemError::UnknownElement { symbol: alpha, span });
                 }
             }
 
             // parenthesis token, need to parse the sequence between the parentheses recursively
-            Token(_, OpeningParenthesis | OpeningBracket, _) => {
+            Token(_, OpeningParenthesis | OpeningBracket, opening_pos) => {
                 let mut sub_seq: Vec<Token> = Vec::new();
                 let mut depth = 1;
                 while depth > 0 && !rem_tokens.is_empty() {
@@ -225,10 +304,10 @@ fn parse_atoms_seq(atoms: &PeriodicTable, tokens: &Vec<Token>) -> Result<(BTreeM
                         err @ Err(_) => return err
                     }
                 } else {
-                    return Err(PositionedError(
-                        format!("unbalanced parentheses"),
-                        None,
-                    ));
+                    return Err(ChemError::UnbalancedParenthesis {
+                        description: format!("unbalanced parentheses"),
+                        span: Some(opening_pos..(opening_pos + 1)),
+                    });
                 }
             }
 
@@ -254,41 +333,132 @@ fn parse_atoms_seq(atoms: &PeriodicTable, tokens: &Vec<Token>) -> Result<(BTreeM
                         if *sign == Plus { charge += 1 } else { charge -= 1 };
                         rem_tokens.remove(0);
                     }
-                    _ => return Err(PositionedError(
-                        format!("charge format error, expected '^<charge><+/->', e.g. '^3+', or '^<+/-><charge>', e.g. '^+3'"),
-                        Some(pos_exp),
-                    ))
+                    _ => return Err(ChemError::ChargeFormat { span: pos_exp..(pos_exp + 1) })
                 }
             }
 
             // unexpected token: format error
-            Token(_, _, pos) => return Err(PositionedError(
-                format!("unexpected: '{}' at position {}", next_tok, pos),
-                Some(pos),
-            ))
+            _ => return Err(ChemError::UnexpectedToken { token: next_tok.0.clone(), span: next_tok.span() })
         }
     }
     Ok((atoms_seq, charge))
 }
 
-/// `periodic_table` - all possible atoms in the molecule
-pub fn parse_molecule(atoms: &PeriodicTable, tokens: &Vec<Token>) -> Result<Molecule, PositionedError> {
-    match check_token_seq(&tokens) {
-        Ok(()) => {
-            parse_atoms_seq(atoms, tokens).map(|(atoms_seq, charge)| {
-                let str_repr: String = tokens.iter().map(|tok| { format!("{}", tok.0) }).collect();
-                Molecule {
-                    atoms: atoms_seq,
-                    charge,
-                    string_repr: Some(str_repr),
+/// Part of an adduct: its multiplier, the token of the multiplier (if any) and the tokens of the part
+type AdductPart = (u32, Option<Token>, Vec<Token>);
+
+/// Splits the tokens of an adduct (e.g. `CuSO4·5H2O`) at the separators that are not between parentheses
+///
+/// Returns the parts with their multiplier, i.e. the number that starts the part (if any, o.w. 1) and that is
+/// removed from the tokens of the part, together with the token of the multiplier
+fn split_adduct_parts(tokens: &Vec<Token>) -> Result<Vec<AdductPart>, ChemError> {
+    let mut parts: Vec<AdductPart> = Vec::new();
+    let mut curr_multiplier: u32 = 1;
+    let mut curr_multiplier_tok: Option<Token> = None;
+    let mut curr_part: Vec<Token> = Vec::new();
+    let mut last_separator: Option<&Token> = None;
+    let mut depth = 0;
+    for tok in tokens {
+        match tok {
+            Token(_, OpeningParenthesis | OpeningBracket, _) => depth += 1,
+            Token(_, ClosingParenthesis | ClosingBracket, _) => depth -= 1,
+            _ => {}
+        }
+        match tok {
+            Token(_, Dot | Adduct, _) if depth == 0 => {
+                if curr_part.is_empty() {
+                    return Err(ChemError::UnexpectedToken { token: tok.0.clone(), span: tok.span() });
                 }
-            })
+                parts.push((curr_multiplier, curr_multiplier_tok.take(), curr_part.clone()));
+                curr_part.clear();
+                curr_multiplier = 1;
+                last_separator = Some(tok);
+            }
+            Token(num, Numeric, _) if curr_part.is_empty() && last_separator.is_some() && curr_multiplier_tok.is_none() => {
+                // 0 and numbers that do not fit in a `u32` cannot be multipliers
+                curr_multiplier = match num.parse() {
+                    Ok(multiplier) if multiplier != 0 => multiplier,
+                    _ => return Err(ChemError::UnexpectedToken { token: num.clone(), span: tok.span() })
+                };
+                curr_multiplier_tok = Some(tok.clone());
+            }
+            _ => curr_part.push(tok.clone())
+        }
+    }
+    if curr_part.is_empty() {
+        if let Some(separator) = last_separator {
+            return Err(ChemError::UnexpectedToken { token: separator.0.clone(), span: separator.span() });
+        }
+    }
+    parts.push((curr_multiplier, curr_multiplier_tok, curr_part));
+    Ok(parts)
+}
+
+/// `periodic_table` - all possible atoms in the molecule
+///
+/// Adducts (e.g. hydrates like `CuSO4·5H2O`, `CuSO4*5H2O` or `CuSO4.5H2O`) are parsed into a single molecule
+/// containing the atoms of all their parts, but keep their notation in `string_repr`
+pub fn parse_molecule(atoms: &PeriodicTable, tokens: &Vec<Token>) -> Result<Molecule, ChemError> {
+    return_on_error!(check_token_seq(tokens));
+    let (tokens, phase) = strip_phase_annotation(tokens);
+    let tokens = &tokens;
+    if is_electron(tokens) {
+        let str_repr: String = tokens.iter().map(|tok| { tok.0.clone() }).collect();
+        return Ok(Molecule { string_repr: Some(str_repr), phase, ..Molecule::electron() });
+    }
+    let mut atoms_seq: BTreeMap<Atom, u32> = BTreeMap::new();
+    let mut charge = 0;
+    for (multiplier, multiplier_tok, part_tokens) in return_on_error!(split_adduct_parts(tokens)) {
+        let (part_atoms_seq, part_charge) = return_on_error!(parse_atoms_seq(atoms, &part_tokens));
+        let overflow_error = || {
+            let tok = multiplier_tok.clone().unwrap();
+            ChemError::UnexpectedToken { token: tok.0.clone(), span: tok.span() }
+        };
+        for (atom, coef) in part_atoms_seq {
+            let multiplied_coef = return_on_error!(multiplier.checked_mul(coef).ok_or_else(overflow_error));
+            merge_atom_into_seq(&mut atoms_seq, atom, multiplied_coef);
+        }
+        let multiplied_charge = return_on_error!(i32::try_from(multiplier).ok().and_then(|m| { part_charge.checked_mul(m) }).ok_or_else(overflow_error));
+        charge += multiplied_charge;
+    }
+    let str_repr: String = tokens.iter().map(|tok| { tok.0.clone() }).collect();
+    Ok(Molecule {
+        atoms: atoms_seq,
+        charge,
+        string_repr: Some(str_repr),
+        phase,
+    })
+}
+
+/// Electrons are written `e^-` or `e-`
+fn is_electron(tokens: &[Token]) -> bool {
+    matches!(
+        tokens,
+        [Token(e, Alphabetic, _), Token(_, Exponent, _), Token(_, Minus, _)] | [Token(e, Alphabetic, _), Token(_, Minus, _)]
+        if e == ELECTRON_SYMBOL
+    )
+}
+
+/// If the tokens end with a state of matter annotation (`(s)`, `(l)`, `(g)` or `(aq)`), returns the tokens before it
+/// and the phase, o.w. returns the tokens unchanged
+fn strip_phase_annotation(tokens: &[Token]) -> (Vec<Token>, Option<Phase>) {
+    if let [
+        molec_tokens @ ..,
+        Token(_, OpeningParenthesis, _),
+        Token(symbol, Alphabetic, _),
+        Token(_, ClosingParenthesis, _)
+    ] = tokens {
+        if let Some(phase) = Phase::of_symbol(symbol) {
+            return (molec_tokens.to_vec(), Some(phase));
         }
-        Err(err) => Err(err)
     }
+    (tokens.to_vec(), None)
 }
 
-fn parse_equation_member(periodic_table: &PeriodicTable, tokens: &Vec<Token>) -> Result<Vec<(Molecule, Option<ChemQuantity>)>, PositionedError> {
+/// `observed` - receives the indices of the molecules whose quantity is followed by `OBSERVED_KEYWORD`
+///
+/// `purities` - receives the purity of each molecule, see `EquationAnnotations`
+fn parse_equation_member(periodic_table: &PeriodicTable, tokens: &Vec<Token>, observed: &mut Vec<usize>, purities: &mut Vec<Option<f64>>) -> Result<Vec<(Molecule, Option<ChemQuantity>)>, ChemError> {
     let mut molecules: Vec<(Molecule, Option<ChemQuantity>)> = Vec::new();
     let mut acc_tokens: Vec<Token> = Vec::new();
 
@@ -307,7 +477,7 @@ fn parse_equation_member(periodic_table: &PeriodicTable, tokens: &Vec<Token>) ->
                     expect_charge_plus_or_minus = false;
                     acc_tokens.push(tok.clone());
                 } else {
-                    let status_res = terminate_molecule(periodic_table, &mut molecules, &mut acc_tokens);
+                    let status_res = terminate_molecule(periodic_table, &mut molecules, &mut acc_tokens, observed, purities);
                     if let Err(err) = status_res { return Err(err); }
                 }
             }
@@ -315,57 +485,310 @@ fn parse_equation_member(periodic_table: &PeriodicTable, tokens: &Vec<Token>) ->
                 if expect_charge_plus_or_minus { expect_charge_plus_or_minus = false }
                 acc_tokens.push(tok.clone());
             }
-            Arrow | NoType => return Err(PositionedError(format!("unexpected: {}", tok.0), Some(tok.2))),
+            Arrow | NoType => return Err(ChemError::UnexpectedToken { token: tok.0.clone(), span: tok.span() }),
             _ => acc_tokens.push(tok.clone())
         }
     }
-    let status_res = terminate_molecule(periodic_table, &mut molecules, &mut acc_tokens);
+    let status_res = terminate_molecule(periodic_table, &mut molecules, &mut acc_tokens, observed, purities);
     if let Err(err) = status_res { Err(err) } else { Ok(molecules) }
 }
 
 /// Tries to parse the molecule described by the tokens in `acc_molec_tokens`, adds it to `molecules` and clears `acc_molec_tokens`
-fn terminate_molecule(periodic_table: &PeriodicTable, molecules: &mut Vec<(Molecule, Option<ChemQuantity>)>, acc_tokens: &mut Vec<Token>) -> Result<(), PositionedError> {
+fn terminate_molecule(periodic_table: &PeriodicTable, molecules: &mut Vec<(Molecule, Option<ChemQuantity>)>, acc_tokens: &mut Vec<Token>, observed: &mut Vec<usize>, purities: &mut Vec<Option<f64>>) -> Result<(), ChemError> {
+    let observed_tok = match acc_tokens.last() {
+        Some(tok @ Token(txt, Alphabetic, _)) if txt == OBSERVED_KEYWORD => Some(tok.clone()),
+        _ => None
+    };
+    if observed_tok.is_some() {
+        acc_tokens.pop();
+    }
+    let purity_tokens: Vec<Token> = match acc_tokens.iter().position(|tok| { tok.1 == At }) {
+        Some(at_idx) => acc_tokens.split_off(at_idx),
+        None => Vec::new()
+    };
+    let purity = if purity_tokens.is_empty() { None } else { Some(return_on_error!(purity_for(&purity_tokens))) };
     let (chem_quant_opt, num_remove) =
         match &acc_tokens[..] {
             [
-            Token(digits_str, Numeric, _),
-            Token(_, Dot, _),
-            Token(decimal_str, Numeric, _),
-            Token(unit_str, Alphabetic, unit_tok_pos),
-            ..
-            ] => (Some(return_on_error!(chem_quantity_for(unit_str, unit_tok_pos, &format!("{}.{}", digits_str, decimal_str)))), 4),
-            [
-            Token(digits_str, Numeric, _),
-            Token(unit_str, Alphabetic, unit_tok_pos),
+            value_tok @ Token(_, Numeric, _),
+            Token(_, Alphabetic, _),
             ..
-            ] => (Some(return_on_error!(chem_quantity_for(unit_str, unit_tok_pos, &digits_str))), 2),
+            ] => {
+                let n_unit_toks = unit_tokens_len(&acc_tokens[1..], is_chem_unit).max(1);
+                let unit_tokens = &acc_tokens[1..(1 + n_unit_toks)];
+                let quantity = return_on_error!(chem_quantity_for(unit_tokens, value_tok));
+                // the quantity may be that of a solution, followed by the concentration of the species, e.g. `25 mL 0.1 M`
+                let (quantity, n_conc_toks) = return_on_error!(solute_quantity_for(quantity, &acc_tokens[..(1 + n_unit_toks)], &acc_tokens[(1 + n_unit_toks)..]));
+                (Some(quantity), 1 + n_unit_toks + n_conc_toks)
+            }
             _ => (None, 0)
         };
+    // the species is weighed with its impurities, only the pure part of the quantity takes part in the reaction
+    let chem_quant_opt = match (chem_quant_opt, purity) {
+        (Some(gross), Some(purity)) => match pure_quantity(&gross, purity) {
+            Some(pure) => Some(pure),
+            None => return Err(ChemError::QuantityLayout {
+                description: format!("the purity of a species is by mass, it only applies to masses, e.g. '100 g CaCO3 {} 92%'", PURITY_SIGN),
+                span: Some(acc_tokens[0].2..purity_tokens[purity_tokens.len() - 1].span().end),
+            })
+        },
+        (quant_opt, _) => quant_opt
+    };
     for _ in 0..num_remove {
         acc_tokens.remove(0);
     }
+    // the other numbers are indices in the molecule, a numeric literal here is a quantity without unit
+    if let Some(tok) = acc_tokens.iter().find(|tok| { tok.1 == Numeric && !tok.0.chars().all(|c| { c.is_ascii_digit() }) }) {
+        return Err(ChemError::QuantityFormat { quantity: tok.0.clone(), span: tok.span() });
+    }
+    if let Some(tok) = observed_tok {
+        if chem_quant_opt.is_none() {
+            return Err(ChemError::QuantityLayout {
+                description: format!("'{}' must follow a species with a quantity, e.g. '4.1 g H2O {}'", OBSERVED_KEYWORD, OBSERVED_KEYWORD),
+                span: Some(tok.span()),
+            });
+        }
+        observed.push(molecules.len());
+    }
     let parsed = parse_molecule(periodic_table, &acc_tokens);
     acc_tokens.clear();
     match parsed {
         Ok(molecule) => {
             molecules.push((molecule, chem_quant_opt));
+            purities.push(purity);
             Ok(())
         }
         Err(err) => Err(err)
     }
 }
 
-fn chem_quantity_for(unit_str: &String, unit_tok_pos: &u64, value_str: &String) -> Result<ChemQuantity, PositionedError> {
-    let value: f64 = value_str.parse().unwrap();
-    let unit = match chem_unit_for(unit_str) {
+/// Returns the number of tokens at the start of `tokens` that form the longest unit accepted by `is_unit` (0 if they
+/// form none)
+///
+/// A unit may span several tokens, e.g. `mL`, `cm³` or `g/L`, and is directly followed by the species
+fn unit_tokens_len(tokens: &[Token], is_unit: fn(&str) -> bool) -> usize {
+    let mut unit_str = String::new();
+    let mut longest = 0;
+    for (idx, tok) in tokens.iter().enumerate() {
+        if tok.1 != Alphabetic && tok.1 != Numeric && tok.1 != UnitSymbol {
+            break;
+        }
+        unit_str.push_str(&tok.0);
+        if is_unit(&unit_str) {
+            longest = idx + 1;
+        }
+    }
+    longest
+}
+
+fn is_chem_unit(txt: &str) -> bool {
+    chem_unit_for(&txt.to_string()).is_ok()
+}
+
+fn is_concentration_unit(txt: &str) -> bool {
+    concentration_for(1.0, txt).is_some()
+}
+
+/// If `conc_tokens` starts with a concentration (e.g. `0.1 M`, `40 g/L` or `10 %`), returns the quantity of the
+/// species in `solution` (described by `solution_tokens`) and the number of tokens of the concentration.
+/// Returns `solution` and 0 o.w.
+fn solute_quantity_for(solution: ChemQuantity, solution_tokens: &[Token], conc_tokens: &[Token]) -> Result<(ChemQuantity, usize), ChemError> {
+    let value_tok = match conc_tokens {
+        [value_tok @ Token(_, Numeric, _), ..] => value_tok,
+        _ => return Ok((solution, 0))
+    };
+    let n_unit_toks = unit_tokens_len(&conc_tokens[1..], is_concentration_unit);
+    if n_unit_toks == 0 {
+        return Ok((solution, 0));
+    }
+    let value = match parse_numeric_literal(&value_tok.0) {
+        Some(value) => value,
+        None => return Err(ChemError::QuantityFormat { quantity: value_tok.0.clone(), span: value_tok.span() })
+    };
+    let unit_str: String = conc_tokens[1..(1 + n_unit_toks)].iter().map(|tok| { tok.0.clone() }).collect();
+    let concentration = concentration_for(value, &unit_str).unwrap();
+    match concentration.solute_quantity(&solution) {
+        Some(solute) => Ok((solute, 1 + n_unit_toks)),
+        None => Err(ChemError::SolutionQuantity {
+            solution: solution.1.to_string(),
+            concentration: unit_str,
+            span: solution_tokens[0].2..conc_tokens[n_unit_toks].span().end,
+        })
+    }
+}
+
+/// Parses the purity introduced by the first token of `purity_tokens` (`PURITY_SIGN`), e.g. `@ 92%`, as a mass fraction
+fn purity_for(purity_tokens: &[Token]) -> Result<f64, ChemError> {
+    let purity_error = || {
+        ChemError::PurityFormat {
+            purity: purity_tokens.iter().map(|tok| { tok.0.clone() }).collect(),
+            span: purity_tokens[0].2..purity_tokens[purity_tokens.len() - 1].span().end,
+        }
+    };
+    match &purity_tokens[1..] {
+        [Token(value_str, Numeric, _), Token(percent, UnitSymbol, _)] if percent == "%" => match parse_numeric_literal(value_str) {
+            Some(value) if value > 0.0 && value <= 100.0 => Ok(value / 100.0),
+            _ => Err(purity_error())
+        },
+        _ => Err(purity_error())
+    }
+}
+
+/// Parses a quantity given alone, e.g. `50 mL`
+pub fn parse_quantity(tokens: &[Token]) -> Result<ChemQuantity, ChemError> {
+    let non_blank_tokens: Vec<Token> = tokens.iter().filter(|tok| { tok.1 != Whitespace }).cloned().collect();
+    match &non_blank_tokens[..] {
+        [value_tok @ Token(_, Numeric, _), unit_tokens @ ..] if !unit_tokens.is_empty() => chem_quantity_for(unit_tokens, value_tok),
+        _ => {
+            let quantity: String = tokens.iter().map(|tok| { tok.0.clone() }).collect();
+            let span = non_blank_tokens.first().map_or(0..0, |first| { first.2..non_blank_tokens[non_blank_tokens.len() - 1].span().end });
+            Err(ChemError::QuantityFormat { quantity: quantity.trim().to_string(), span })
+        }
+    }
+}
+
+/// Separator between the temperature and the pressure of gas conditions
+const CONDITIONS_SEPARATOR: &str = ",";
+
+/// Parses the temperature and/or the pressure of gases, e.g. `298 K, 1 atm`, `25 °C` or `2 bar`
+///
+/// The condition that is not given is that of `GasConditions::STP`
+pub fn parse_gas_conditions(tokens: &[Token]) -> Result<GasConditions, ChemError> {
+    let mut temperature: Option<f64> = None;
+    let mut pressure: Option<f64> = None;
+    for raw_part in tokens.split(|tok| { tok.0 == CONDITIONS_SEPARATOR }) {
+        let part_str: String = raw_part.iter().map(|tok| { tok.0.clone() }).collect::<String>().trim().to_string();
+        let part: Vec<&Token> = raw_part.iter().filter(|tok| { tok.1 != Whitespace }).collect();
+        let format_error = || { ChemError::ConditionsFormat {
+            conditions: part_str.clone(),
+            span: part.first().map_or(0..0, |first| { first.2..part[part.len() - 1].span().end }),
+        } };
+        let (value_tok, unit_tokens) = match part.split_first() {
+            Some((value_tok @ Token(_, Numeric, _), unit_tokens)) if !unit_tokens.is_empty() => (value_tok, unit_tokens),
+            _ => return Err(format_error())
+        };
+        // temperatures in °C may be negative
+        let value = match value_tok.0.strip_prefix('-') {
+            Some(abs_value_str) => parse_numeric_literal(abs_value_str).map(|abs_value| { -abs_value }),
+            None => parse_numeric_literal(&value_tok.0)
+        };
+        let value = return_on_error!(value.ok_or_else(format_error));
+        let unit_str: String = unit_tokens.iter().map(|tok| { tok.0.clone() }).collect();
+        match (temperature_for(value, &unit_str), pressure_for(value, &unit_str)) {
+            (Some(kelvins), _) if temperature.is_none() => temperature = Some(kelvins),
+            (_, Some(pascals)) if pressure.is_none() && pascals > 0.0 => pressure = Some(pascals),
+            _ => return Err(format_error())
+        }
+    }
+    Ok(GasConditions {
+        temperature: temperature.unwrap_or(GasConditions::STP.temperature),
+        pressure: pressure.unwrap_or(GasConditions::STP.pressure),
+    })
+}
+
+/// Keyword that introduces the temperature and pressure of the gases after an equation, e.g. ` at 25 °C, 1 atm`
+const GAS_CONDITIONS_KEYWORD: &str = " at ";
+
+/// Keyword that introduces the final volume of solution after an equation, e.g. ` in 50 mL`
+const FINAL_VOLUME_KEYWORD: &str = " in ";
+
+/// Conditions of a reaction, that may follow its equation
+#[derive(Debug, Clone)]
+pub struct ReactionConditions {
+    /// `GasConditions::STP` if not given
+    pub gases: GasConditions,
+    /// final volume of solution, in which the concentrations of the products are computed
+    pub final_volume: Option<ChemQuantity>,
+}
+
+/// Splits `txt` into the equation and the conditions that follow it, in any order: `at <temperature>, <pressure>`
+/// (see `parse_gas_conditions`) and `in <volume>`, e.g. `H2 + O2 => H2O at 25 °C in 1 L`
+///
+/// The spans of the errors are positions in `txt`
+pub fn split_reaction_conditions(txt: &str) -> Result<(&str, ReactionConditions), ChemError> {
+    let mut eq_txt = txt;
+    let mut gases: Option<GasConditions> = None;
+    let mut final_volume: Option<ChemQuantity> = None;
+    // the conditions are removed from the end of the text, the last one first
+    loop {
+        let last_keyword = [GAS_CONDITIONS_KEYWORD, FINAL_VOLUME_KEYWORD].into_iter()
+            .filter_map(|keyword| { eq_txt.rfind(keyword).map(|idx| { (idx, keyword) }) })
+            .max_by_key(|(idx, _)| { *idx });
+        let (keyword_idx, keyword) = match last_keyword {
+            Some(found) => found,
+            None => break
+        };
+        let args_txt = &eq_txt[(keyword_idx + keyword.len())..];
+        // spans are counted in characters, as by `tokenize`
+        let offset = eq_txt[..(keyword_idx + keyword.len())].chars().count() as u64;
+        let span = (eq_txt[..(keyword_idx + 1)].chars().count() as u64)..(eq_txt.chars().count() as u64);
+        let already_given = if keyword == GAS_CONDITIONS_KEYWORD { gases.is_some() } else { final_volume.is_some() };
+        if already_given {
+            return Err(ChemError::QuantityLayout { description: format!("'{}' given twice", keyword.trim()), span: Some(span) });
+        }
+        let tokens = tokenize(&args_txt.to_string());
+        if keyword == GAS_CONDITIONS_KEYWORD {
+            gases = Some(return_on_error!(parse_gas_conditions(&tokens).map_err(|err| { err.shifted(offset) })));
+        } else {
+            let volume = return_on_error!(parse_quantity(&tokens).map_err(|err| { err.shifted(offset) }));
+            if volume.1.dimension() != Dimension::Volume {
+                return Err(ChemError::QuantityLayout {
+                    description: format!("expected a volume of solution after '{}', e.g. '50 mL'", keyword.trim()),
+                    span: Some(offset..span.end),
+                });
+            }
+            if volume.0 <= 0.0 {
+                return Err(ChemError::QuantityLayout {
+                    description: format!("the volume of solution after '{}' must be positive", keyword.trim()),
+                    span: Some(offset..span.end),
+                });
+            }
+            final_volume = Some(volume);
+        }
+        eq_txt = &eq_txt[..keyword_idx];
+    }
+    Ok((eq_txt, ReactionConditions { gases: gases.unwrap_or(GasConditions::STP), final_volume }))
+}
+
+fn chem_quantity_for(unit_tokens: &[Token], value_tok: &Token) -> Result<ChemQuantity, ChemError> {
+    let value = match parse_numeric_literal(&value_tok.0) {
+        Some(value) => value,
+        None => return Err(ChemError::QuantityFormat { quantity: value_tok.0.clone(), span: value_tok.span() })
+    };
+    let unit_str: String = unit_tokens.iter().map(|tok| { tok.0.clone() }).collect();
+    let unit = match chem_unit_for(&unit_str) {
         Ok(u) => u,
-        Err(_) => return Err(PositionedError(format!("unknown unit: {}", unit_str), Some(*unit_tok_pos)))
+        Err(_) => return Err(ChemError::UnknownUnit {
+            unit: unit_str,
+            span: unit_tokens[0].2..unit_tokens[unit_tokens.len() - 1].span().end,
+        })
     };
-    Ok(ChemQuantity(value, unit))
+    Ok(ChemQuantity(value, unit, 0.0))
 }
 
 /// `periodic_table` - all possible atoms in the molecule
-pub fn parse_quantified_equation(periodic_table: &PeriodicTable, tokens: &Vec<Token>) -> Result<QuantifiedEquation, PositionedError> {
+///
+/// Species may be annotated as observed or with their purity, see `parse_annotated_equation`
+pub fn parse_quantified_equation(periodic_table: &PeriodicTable, tokens: &Vec<Token>) -> Result<QuantifiedEquation, ChemError> {
+    parse_annotated_equation(periodic_table, tokens).map(|(quant_eq, _)| { quant_eq })
+}
+
+/// Annotations that follow the species of an equation, besides their quantities
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EquationAnnotations {
+    /// indices of the products whose quantity is an observed one, e.g. `4.1 g H2O observed`
+    pub observed_products: Vec<usize>,
+    /// purity of each reactant as a mass fraction, e.g. 0.92 for `100 g CaCO3 @ 92%`, `None` for a pure species
+    pub lhs_purities: Vec<Option<f64>>,
+    /// purity of each product, as for the reactants
+    pub rhs_purities: Vec<Option<f64>>,
+}
+
+/// Parses an equation whose species may be annotated, as described by `EquationAnnotations`
+///
+/// The observed quantities are still present in the returned equation. The quantities of impure species are those
+/// of the pure species they contain, e.g. 92 g for `100 g CaCO3 @ 92%`
+pub fn parse_annotated_equation(periodic_table: &PeriodicTable, tokens: &Vec<Token>) -> Result<(QuantifiedEquation, EquationAnnotations), ChemError> {
     let mut lhs_tokens: Vec<Token> = Vec::new();
     let mut rhs_tokens: Vec<Token> = Vec::new();
     let mut arrow: String = String::new();
@@ -380,38 +803,80 @@ pub fn parse_quantified_equation(periodic_table: &PeriodicTable, tokens: &Vec<To
                 match member_idx {
                     0 => lhs_tokens.push(tok.clone()),
                     1 => rhs_tokens.push(tok.clone()),
-                    _ => return Err(PositionedError(
-                        format!("more than 2 members in equation"),
-                        Some(tok.2),
-                    ))
+                    _ => return Err(ChemError::EquationLayout {
+                        description: format!("more than 2 members in equation"),
+                        span: Some(tok.span()),
+                    })
                 }
             }
         }
     }
     if member_idx == 1 {
+        let mut observed_reactants: Vec<usize> = Vec::new();
+        let mut annotations = EquationAnnotations::default();
         let (lhs, rhs) = match (
-            parse_equation_member(periodic_table, &lhs_tokens),
-            parse_equation_member(periodic_table, &rhs_tokens)
+            parse_equation_member(periodic_table, &lhs_tokens, &mut observed_reactants, &mut annotations.lhs_purities),
+            parse_equation_member(periodic_table, &rhs_tokens, &mut annotations.observed_products, &mut annotations.rhs_purities)
         ) {
             (Ok(l), Ok(r)) => (l, r),
             (Err(err), _) => return Err(err),
             (_, Err(err)) => return Err(err)
         };
-        Ok(QuantifiedEquation { lhs, rhs, arrow })
+        if !observed_reactants.is_empty() {
+            return Err(ChemError::QuantityLayout {
+                description: format!("only the quantities of products can be '{}'", OBSERVED_KEYWORD),
+                span: lhs_tokens.iter().find(|tok| { tok.0 == OBSERVED_KEYWORD }).map(|tok| { tok.span() }),
+            });
+        }
+        Ok((QuantifiedEquation { lhs, rhs, arrow }, annotations))
     } else {
-        Err(PositionedError(
-            format!("an equation must have exactly 2 members"),
-            None,
-        ))
+        Err(ChemError::EquationLayout {
+            description: format!("an equation must have exactly 2 members"),
+            span: None,
+        })
     }
 }
 
-pub fn parse_raw_equation(periodic_table: &PeriodicTable, tokens: &Vec<Token>) -> Result<RawEquation, PositionedError> {
+pub fn parse_raw_equation(periodic_table: &PeriodicTable, tokens: &Vec<Token>) -> Result<RawEquation, ChemError> {
     let quant_eq = return_on_error!(parse_quantified_equation(periodic_table, tokens));
     if quant_eq.is_raw_eq() {
         Ok(quant_eq.to_raw_eq())
     }
     else {
-        Err(PositionedError(format!("expected chemical equation without coefficients or quantities"), None))
+        Err(ChemError::EquationLayout {
+            description: format!("expected chemical equation without coefficients or quantities"),
+            span: None,
+        })
+    }
+}
+
+/// Sets the position of each element in `unconserved` to the position of its first occurrence in the member of the
+/// equation (described by `tokens`) in which it appears
+pub fn locate_unconserved_elements(tokens: &Vec<Token>, unconserved: &[UnconservedElement]) -> Vec<UnconservedElement> {
+    unconserved.iter().map(|elem| {
+        let mut side = EquationSide::Reactants;
+        let mut position: Option<u64> = None;
+        for tok in tokens {
+            match tok {
+                Token(_, Arrow, _) => side = EquationSide::Products,
+                Token(txt, Alphabetic, pos) if side == elem.side && *txt == elem.atom.code => {
+                    position = Some(*pos);
+                    break;
+                }
+                _ => {}
+            }
+        }
+        UnconservedElement { position, ..elem.clone() }
+    }).collect()
+}
+
+/// Checks that every element of `raw_eq` appears in both of its members, reporting the elements that do not with
+/// their position in `tokens`, the tokens from which `raw_eq` was parsed
+pub fn check_element_conservation(raw_eq: &RawEquation, tokens: &Vec<Token>) -> Result<(), ChemError> {
+    let unconserved = unconserved_elements(raw_eq);
+    if unconserved.is_empty() {
+        Ok(())
+    } else {
+        Err(ChemError::UnconservedElements(locate_unconserved_elements(tokens, &unconserved)))
     }
 }
@@ -0,0 +1,392 @@
+use std::collections::btree_map::BTreeMap;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use num_integer::Integer;
+use num_rational::Rational64;
+use num_traits::Signed;
+use crate::chemistry::{Atom, balance, balance_in_medium, balance_with_movable_species, BalancedEquation, EquationSide, Medium, Molecule, PeriodicTable, RawEquation};
+use crate::elements::ElementProperties;
+use crate::error::ChemError;
+use crate::return_on_error;
+
+const HYDROGEN: &str = "H";
+const OXYGEN: &str = "O";
+const FLUORINE: &str = "F";
+
+/// Assigns an oxidation state to each element of `molecule`, such that the states sum up to the charge of the molecule
+///
+/// The rules are applied in this order, the last element that has no state yet taking the remaining charge:
+/// - F is -1
+/// - alkali metals are +1, alkaline earth metals are +2
+/// - H is +1, or -1 (hydride) if all the other elements are less electronegative
+/// - O is -2 (unless it takes the remaining charge, e.g. in peroxides)
+/// - the nonmetals complete their octets (e.g. -1 for Cl), from the most electronegative one, except the least
+///   electronegative one, which takes the remaining charge if no metal is left, and otherwise loses as many valence
+///   electrons as it can while leaving a positive state to the metals (e.g. +6 for S in CuSO4, +2 for C in K4Fe(CN)6)
+/// - if only metals are left, they share the remaining charge
+///
+/// States are averaged over the atoms of an element, so they may be fractional (e.g. +8/3 for Fe in Fe3O4)
+pub fn oxidation_states(molecule: &Molecule, elements: &[ElementProperties]) -> BTreeMap<Atom, Rational64> {
+    let properties = |atom: &Atom| { elements.iter().find(|elem| { elem.atom == *atom }) };
+    let mut states: BTreeMap<Atom, Rational64> = BTreeMap::new();
+    let unassigned = |states: &BTreeMap<Atom, Rational64>| -> Vec<Atom> {
+        molecule.atoms.keys().filter(|atom| { !states.contains_key(atom) }).cloned().collect()
+    };
+    // returns the priority of the rule (lower is applied first) and the state
+    let fixed_state = |atom: &Atom, others: &[Atom]| -> Option<(u32, i64)> {
+        let props = properties(atom);
+        let is_metal_of_group = |group: u32| { props.is_some_and(|props| { props.metal && props.group == Some(group) }) };
+        if atom.code == FLUORINE {
+            Some((0, -1))
+        } else if is_metal_of_group(1) {
+            Some((1, 1))
+        } else if is_metal_of_group(2) {
+            Some((1, 2))
+        } else if atom.code == HYDROGEN {
+            let hydrogen_en = electronegativity(props);
+            let is_hydride = others.iter().all(|other| { electronegativity(properties(other)) < hydrogen_en });
+            Some((2, if is_hydride { -1 } else { 1 }))
+        } else if atom.code == OXYGEN {
+            Some((3, -2))
+        } else {
+            None
+        }
+    };
+    let remaining_charge = |states: &BTreeMap<Atom, Rational64>| -> Rational64 {
+        let assigned_charge: Rational64 = states.iter()
+            .map(|(atom, state)| { state * Rational64::from(molecule.atoms[atom] as i64) })
+            .sum();
+        Rational64::from(molecule.charge as i64) - assigned_charge
+    };
+    loop {
+        let remaining = unassigned(&states);
+        if remaining.is_empty() {
+            break;
+        }
+        if remaining.len() == 1 {
+            let last = &remaining[0];
+            states.insert(last.clone(), remaining_charge(&states) / Rational64::from(molecule.atoms[last] as i64));
+            continue;
+        }
+        let others = |atom: &Atom| -> Vec<Atom> {
+            molecule.atoms.keys().filter(|other| { *other != atom }).cloned().collect()
+        };
+        let fixed = remaining.iter()
+            .filter_map(|atom| { fixed_state(atom, &others(atom)).map(|(priority, state)| { (priority, atom, state) }) })
+            .min_by_key(|(priority, _, _)| { *priority });
+        if let Some((_, atom, state)) = fixed {
+            states.insert(atom.clone(), Rational64::from(state));
+            continue;
+        }
+        let mut nonmetals: Vec<(&Atom, (i64, i64))> = remaining.iter()
+            .filter_map(|atom| { nonmetal_states(properties(atom)).map(|bounds| { (atom, bounds) }) })
+            .collect();
+        nonmetals.sort_by(|(a, _), (b, _)| { electronegativity(properties(b)).total_cmp(&electronegativity(properties(a))) });
+        match nonmetals.as_slice() {
+            [] => {
+                // only metals are left, they share the remaining charge
+                let n_remaining_atoms: u32 = remaining.iter().map(|atom| { molecule.atoms[atom] }).sum();
+                let shared_state = remaining_charge(&states) / Rational64::from(n_remaining_atoms as i64);
+                for atom in remaining {
+                    states.insert(atom, shared_state);
+                }
+            }
+            [(nonmetal, (octet_state, highest_state))] => {
+                // the other remaining elements are metals, whose atoms must keep a positive state on the whole
+                let n_nonmetal_atoms = Rational64::from(molecule.atoms[*nonmetal] as i64);
+                let remaining_charge = remaining_charge(&states);
+                let state = (*octet_state..=*highest_state).rev()
+                    .find(|&state| { remaining_charge - n_nonmetal_atoms * Rational64::from(state) > Rational64::from(0) })
+                    .unwrap_or(*octet_state);
+                states.insert((*nonmetal).clone(), Rational64::from(state));
+            }
+            [(most_electronegative, (octet_state, _)), ..] => {
+                states.insert((*most_electronegative).clone(), Rational64::from(*octet_state));
+            }
+        }
+    }
+    states
+}
+
+/// Elements of unknown electronegativity (e.g. noble gases) are considered the least electronegative
+fn electronegativity(props: Option<&ElementProperties>) -> f64 {
+    props.and_then(|props| { props.electronegativity }).unwrap_or(f64::NEG_INFINITY)
+}
+
+/// Oxidation states of a nonmetal (or metalloid) of groups 13 to 17 when it completes its octet (e.g. -3 for N) and when
+/// it loses all its valence electrons (e.g. +5 for N), `None` for other elements
+fn nonmetal_states(props: Option<&ElementProperties>) -> Option<(i64, i64)> {
+    match props {
+        Some(ElementProperties { group: Some(group), nonmetal, metalloid, .. })
+        if (*nonmetal || *metalloid) && (13..=17).contains(group) => {
+            let group = *group as i64;
+            Some(((group - 18).max(-4), group - 10))
+        }
+        _ => None
+    }
+}
+
+/// Formats the state with an explicit sign, e.g. `+8/3` or `-2`
+pub fn format_oxidation_state(state: &Rational64) -> String {
+    if state.is_positive() { format!("+{}", state) } else { state.to_string() }
+}
+
+/// Change of the oxidation state of an element between a reactant and a product
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct OxidationStateChange {
+    pub atom: Atom,
+    pub reactant: Molecule,
+    pub product: Molecule,
+    pub before: Rational64,
+    pub after: Rational64,
+    /// number of atoms whose state changes, per reaction unit of the balanced equation
+    pub n_atoms: i64,
+}
+
+impl OxidationStateChange {
+    pub fn is_oxidation(&self) -> bool {
+        self.after > self.before
+    }
+
+    /// Electrons lost (oxidation) or gained (reduction) per reaction unit
+    pub fn n_electrons(&self) -> Rational64 {
+        (self.after - self.before).abs() * Rational64::from(self.n_atoms)
+    }
+}
+
+impl Display for OxidationStateChange {
+    /// E.g. `Fe: +2 (Fe^2+) -> +3 (Fe^3+), oxidized`
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} ({}) -> {} ({}), {}",
+               self.atom.code,
+               format_oxidation_state(&self.before), self.reactant,
+               format_oxidation_state(&self.after), self.product,
+               if self.is_oxidation() { "oxidized" } else { "reduced" }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RedoxReport {
+    pub changes: Vec<OxidationStateChange>,
+    /// reactants containing a reduced element
+    pub oxidizing_agents: Vec<Molecule>,
+    /// reactants containing an oxidized element
+    pub reducing_agents: Vec<Molecule>,
+    /// electrons transferred from the reducing agents to the oxidizing agents, per reaction unit
+    pub n_electrons: Rational64,
+}
+
+impl RedoxReport {
+    pub fn is_redox(&self) -> bool {
+        !self.changes.is_empty()
+    }
+}
+
+/// Occurrence of an element in a species of a balanced equation
+struct Occurrence<'a> {
+    molecule: &'a Molecule,
+    state: Rational64,
+    n_atoms: i64,
+}
+
+fn occurrences<'a>(member: &'a [(Molecule, i32)], atom: &Atom, elements: &[ElementProperties]) -> Vec<Occurrence<'a>> {
+    member.iter()
+        .filter_map(|(molec, coef)| {
+            let n_atoms = *molec.atoms.get(atom)? as i64 * *coef as i64;
+            Some(Occurrence { molecule: molec, state: oxidation_states(molec, elements)[atom], n_atoms })
+        })
+        .collect()
+}
+
+/// Determines the elements of `balanced_eq` that change oxidation state, and the species that they belong to
+///
+/// For each element, the occurrences whose state is found on both sides are considered unchanged, and each remaining
+/// reactant occurrence is paired with each remaining product occurrence (or with all the occurrences of the other side
+/// if none remains there). The atoms of an occurrence that has several partners (e.g. Cl2 in
+/// `3 Cl2 + 6 OH^- => 5 Cl^- + ClO3^- + 3 H2O`) are counted in the partners
+pub fn redox_report(balanced_eq: &BalancedEquation, elements: &[ElementProperties]) -> RedoxReport {
+    let atoms: BTreeSet<Atom> = balanced_eq.lhs.iter().flat_map(|(molec, _)| { molec.atoms.keys().cloned() }).collect();
+    let mut changes: Vec<OxidationStateChange> = Vec::new();
+    for atom in atoms {
+        let reactants = occurrences(&balanced_eq.lhs, &atom, elements);
+        let products = occurrences(&balanced_eq.rhs, &atom, elements);
+        let changed = |occs: &Vec<Occurrence>, others: &Vec<Occurrence>| -> Vec<usize> {
+            (0..occs.len()).filter(|&i| { !others.iter().any(|other| { other.state == occs[i].state }) }).collect()
+        };
+        let changed_reactants = changed(&reactants, &products);
+        let changed_products = changed(&products, &reactants);
+        // pairs of reactant and product indices, with the side whose atoms are counted
+        let mut pairs: Vec<(usize, usize, EquationSide)> = Vec::new();
+        if changed_reactants.is_empty() {
+            // e.g. N in `3 Cu + 8 HNO3 => 3 Cu(NO3)2 + 2 NO + 4 H2O`, where a part of HNO3 is unchanged
+            for &p in &changed_products {
+                pairs.extend((0..reactants.len()).map(|r| { (r, p, EquationSide::Products) }));
+            }
+        } else if changed_products.is_empty() {
+            for &r in &changed_reactants {
+                pairs.extend((0..products.len()).map(|p| { (r, p, EquationSide::Reactants) }));
+            }
+        } else {
+            let counted_side = if changed_products.len() > 1 { EquationSide::Products } else { EquationSide::Reactants };
+            for &r in &changed_reactants {
+                pairs.extend(changed_products.iter().map(|&p| { (r, p, counted_side) }));
+            }
+        }
+        for (r, p, counted_side) in pairs {
+            let (reactant, product) = (&reactants[r], &products[p]);
+            if reactant.state == product.state {
+                continue;
+            }
+            changes.push(OxidationStateChange {
+                atom: atom.clone(),
+                reactant: reactant.molecule.clone(),
+                product: product.molecule.clone(),
+                before: reactant.state,
+                after: product.state,
+                n_atoms: match counted_side {
+                    EquationSide::Reactants => reactant.n_atoms,
+                    EquationSide::Products => product.n_atoms
+                },
+            });
+        }
+    }
+    let agents = |oxidation: bool| -> Vec<Molecule> {
+        let mut agents: Vec<Molecule> = Vec::new();
+        for change in changes.iter().filter(|change| { change.is_oxidation() == oxidation }) {
+            if !agents.contains(&change.reactant) {
+                agents.push(change.reactant.clone());
+            }
+        }
+        agents
+    };
+    RedoxReport {
+        oxidizing_agents: agents(false),
+        reducing_agents: agents(true),
+        n_electrons: changes.iter().filter(|change| { change.is_oxidation() }).map(|change| { change.n_electrons() }).sum(),
+        changes,
+    }
+}
+
+/// Oxidation and reduction half-reactions of a redox equation, and the multipliers that recombine them into the equation
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct HalfReactions {
+    pub oxidation: BalancedEquation,
+    pub reduction: BalancedEquation,
+    pub oxidation_multiplier: i32,
+    pub reduction_multiplier: i32,
+    /// sum of the multiplied half-reactions, where the electrons and the species found on both sides cancel out
+    pub equation: BalancedEquation,
+}
+
+impl Display for HalfReactions {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        return_on_error!(writeln!(f, "oxidation: {} (x{})", self.oxidation, self.oxidation_multiplier));
+        return_on_error!(writeln!(f, "reduction: {} (x{})", self.reduction, self.reduction_multiplier));
+        write!(f, "overall: {}", self.equation)
+    }
+}
+
+/// Splits `raw_eq` into its oxidation and reduction half-reactions, each balanced with electrons
+///
+/// Each half-reaction contains the species whose elements are oxidized (resp. reduced) in `redox_report`, and the
+/// species of the equation that take part in no oxidation state change are added where needed (e.g. `H^+` and `H2O`).
+/// If `medium` is given, its species are also available, and the equation itself is balanced with `balance_in_medium`
+pub fn half_reactions(raw_eq: &RawEquation, medium: Option<Medium>, periodic_table: &PeriodicTable,
+                      elements: &[ElementProperties]) -> Result<HalfReactions, ChemError> {
+    let balanced_eq = return_on_error!(match medium {
+        Some(medium) => balance_in_medium(raw_eq, medium, periodic_table),
+        None => balance(raw_eq)
+    });
+    let report = redox_report(&balanced_eq, elements);
+    if !report.is_redox() {
+        return Err(ChemError::EquationLayout {
+            description: "no element changes oxidation state, the equation has no half-reactions".to_string(),
+            span: None,
+        });
+    }
+    let medium_species = return_on_error!(medium.map_or(Ok(Vec::new()), |medium| { medium.species(periodic_table) }));
+    let mut all_species: Vec<Molecule> = Vec::new();
+    for molec in balanced_eq.lhs.iter().chain(balanced_eq.rhs.iter()).map(|(molec, _)| { molec }).chain(medium_species.iter()) {
+        if !all_species.iter().any(|species| { species.atoms == molec.atoms && species.charge == molec.charge }) {
+            all_species.push(molec.clone());
+        }
+    }
+    let is_core = |molec: &Molecule| -> bool {
+        report.changes.iter().any(|change| { change.reactant == *molec || change.product == *molec })
+    };
+    let auxiliary_species: Vec<Molecule> = all_species.iter().filter(|molec| { !is_core(molec) }).cloned().collect();
+    let half_reaction = |oxidation: bool| -> Result<BalancedEquation, ChemError> {
+        let mut half_eq = RawEquation { lhs: Vec::new(), rhs: Vec::new(), arrow: raw_eq.arrow.clone() };
+        for change in report.changes.iter().filter(|change| { change.is_oxidation() == oxidation }) {
+            if !half_eq.lhs.contains(&change.reactant) {
+                half_eq.lhs.push(change.reactant.clone());
+            }
+            if !half_eq.rhs.contains(&change.product) {
+                half_eq.rhs.push(change.product.clone());
+            }
+        }
+        if oxidation { half_eq.rhs.push(Molecule::electron()) } else { half_eq.lhs.push(Molecule::electron()) }
+        match balance_with_movable_species(&half_eq, &auxiliary_species) {
+            Ok(balanced_half_eq) => Ok(electrons_last(balanced_half_eq)),
+            Err(ChemError::Inconsistent) | Err(ChemError::Underconstrained { .. }) => Err(ChemError::EquationLayout {
+                description: format!(
+                    "cannot balance the {} half-reaction with the species of the equation, spectator ions should be omitted",
+                    if oxidation { "oxidation" } else { "reduction" }
+                ),
+                span: None,
+            }),
+            Err(err) => Err(err)
+        }
+    };
+    let oxidation = return_on_error!(half_reaction(true));
+    let reduction = return_on_error!(half_reaction(false));
+    let n_electrons = |half_eq: &BalancedEquation| -> i32 {
+        half_eq.lhs.iter().chain(half_eq.rhs.iter())
+            .filter(|(molec, _)| { molec.is_electron() })
+            .map(|(_, coef)| { *coef })
+            .sum()
+    };
+    let common_n_electrons = n_electrons(&oxidation).lcm(&n_electrons(&reduction));
+    let oxidation_multiplier = common_n_electrons / n_electrons(&oxidation);
+    let reduction_multiplier = common_n_electrons / n_electrons(&reduction);
+    let equation = sum_of_half_reactions(
+        &[(&oxidation, oxidation_multiplier), (&reduction, reduction_multiplier)], &all_species, &raw_eq.arrow
+    );
+    Ok(HalfReactions { oxidation, reduction, oxidation_multiplier, reduction_multiplier, equation })
+}
+
+fn electrons_last(half_eq: BalancedEquation) -> BalancedEquation {
+    let reordered = |member: Vec<(Molecule, i32)>| -> Vec<(Molecule, i32)> {
+        let (mut species, electrons): (Vec<_>, Vec<_>) = member.into_iter().partition(|(molec, _)| { !molec.is_electron() });
+        species.extend(electrons);
+        species
+    };
+    BalancedEquation { lhs: reordered(half_eq.lhs), rhs: reordered(half_eq.rhs), arrow: half_eq.arrow }
+}
+
+/// Adds up the multiplied half-reactions and simplifies the result, species are ordered as in `all_species`
+fn sum_of_half_reactions(multiplied: &[(&BalancedEquation, i32)], all_species: &[Molecule], arrow: &str) -> BalancedEquation {
+    // positive for the reactants, negative for the products
+    let net_coef = |species: &Molecule| -> i32 {
+        multiplied.iter()
+            .map(|(half_eq, multiplier)| {
+                let coef_in = |member: &Vec<(Molecule, i32)>| -> i32 {
+                    member.iter().filter(|(molec, _)| { molec == species }).map(|(_, coef)| { *coef }).sum()
+                };
+                multiplier * (coef_in(&half_eq.lhs) - coef_in(&half_eq.rhs))
+            })
+            .sum()
+    };
+    let net_coefs: Vec<(Molecule, i32)> = all_species.iter()
+        .map(|species| { (species.clone(), net_coef(species)) })
+        .filter(|(_, coef)| { *coef != 0 })
+        .collect();
+    let divisor = net_coefs.iter().fold(0, |acc, (_, coef)| { acc.gcd(coef) }).max(1);
+    BalancedEquation {
+        lhs: net_coefs.iter().filter(|(_, coef)| { *coef > 0 }).map(|(molec, coef)| { (molec.clone(), coef / divisor) }).collect(),
+        rhs: net_coefs.iter().filter(|(_, coef)| { *coef < 0 }).map(|(molec, coef)| { (molec.clone(), -coef / divisor) }).collect(),
+        arrow: arrow.to_string(),
+    }
+}
@@ -1,4 +1,4 @@
-use Stoichio_calc::arith::{gcd, gcd_vec, lcm, lcm_vec};
+use Stoichio_calc::arith::{compositions, gcd, gcd_vec, lcm, lcm_vec};
 
 #[test]
 fn gcd_72_30_test(){
@@ -40,3 +40,22 @@ fn lcm_vect_22_33_51_8(){
     // 2*11   3*11   3*17   2*2*2  ->  2*2*2*11*3*17
     assert_eq!(2*11*3*17*4, lcm_vec(&Vec::from([22, 33, 51, 8])))
 }
+
+#[test]
+fn compositions_5_in_3_parts_test(){
+    let expected = Vec::from([
+        Vec::from([1, 1, 3]), Vec::from([1, 2, 2]), Vec::from([1, 3, 1]),
+        Vec::from([2, 1, 2]), Vec::from([2, 2, 1]), Vec::from([3, 1, 1])
+    ]);
+    assert_eq!(expected, compositions(5, 3).collect::<Vec<Vec<i64>>>())
+}
+
+#[test]
+fn compositions_edge_cases_test(){
+    assert_eq!(Vec::from([Vec::<i64>::new()]), compositions(0, 0).collect::<Vec<Vec<i64>>>());
+    assert!(compositions(2, 3).next().is_none());
+    assert_eq!(Vec::from([Vec::from([4])]), compositions(4, 1).collect::<Vec<Vec<i64>>>());
+    assert_eq!(84, compositions(10, 4).count());
+    // lazy: the first composition is available without enumerating the others
+    assert_eq!(Some(Vec::from([1, 1, 1, 1, 1, 1, 1, 1, 1, 191])), compositions(200, 10).next());
+}
@@ -9,8 +9,8 @@ mod test_molecules;
 #[cfg(test)]
 mod chemistry_tests {
     use std::collections::btree_map::BTreeMap;
-    use Stoichio_calc::chemistry::{balance, BalancedEquation, ChemQuantity, compute_lhs_coefs, Molecule, QuantifiedEquation, RawEquation};
-    use Stoichio_calc::chemistry::ChemUnit::{Gram, Milligram, Mol};
+    use Stoichio_calc::chemistry::{balance, balance_all, balance_with, BalancedEquation, BalancingCriterion, BaseUnit, chem_unit_for, ChemQuantity, ChemUnit, compute_lhs_coefs, compute_yields, Concentration, concentration_for, ConcentrationUnit, convert, convert_at, Dimension, EquationSide, format_with_uncertainty, GasConditions, gross_quantity, molar_concentration, Molecule, Phase, Prefix, pure_quantity, QuantifiedEquation, RawEquation, unconserved_elements, UnconservedElement};
+    use Stoichio_calc::error::ChemError;
     use crate::test_atoms;
     use crate::test_molecules::{c6h12o6, co2, h2o, o2};
 
@@ -23,9 +23,150 @@ mod chemistry_tests {
                 (test_atoms::oxygen(), 6)
             ]),
             charge: 0,
-            string_repr: None
+            string_repr: None,
+            phase: None
         };
-        assert_near(180.16, molecule.mass_amu(), 0.05)
+        assert_eq!(180_155_880_000, molecule.mass_nano_amu());
+        assert_near(180.1559, molecule.mass_amu(), 0.00005)
+    }
+
+    #[test]
+    fn molecular_mass_uncertainty_c6h12o6_test(){
+        // 6 * u(C), 12 * u(H) and 6 * u(O) are summed in quadrature
+        let expected = ((6.0 * 0.0008_f64).powi(2) + (12.0 * 0.00007_f64).powi(2) + (6.0 * 0.0003_f64).powi(2)).sqrt();
+        assert_near(expected, c6h12o6().mass_uncertainty_amu(), 1e-9)
+    }
+
+    #[test]
+    fn quantities_to_grams_uncertainty_test(){
+        let eq = QuantifiedEquation {
+            lhs: Vec::from([ (c6h12o6(), Some(ChemQuantity(2.0, ChemUnit::MOL, 0.0))) ]),
+            rhs: Vec::from([ (c6h12o6(), Some(ChemQuantity(2.0, ChemUnit::MOL, 0.1))) ]),
+            arrow: "=>".to_string()
+        };
+        let grams_eq = eq.quantities_to_grams().unwrap();
+        let ChemQuantity(exact_value, exact_unit, exact_uncertainty) = grams_eq.lhs[0].1.clone().unwrap();
+        assert_eq!(ChemUnit::GRAM, exact_unit);
+        assert_near(2.0 * 180.15588, exact_value, 1e-9);
+        assert_near(2.0 * c6h12o6().mass_uncertainty_amu(), exact_uncertainty, 1e-9);
+        let ChemQuantity(_, _, uncertainty) = grams_eq.rhs[0].1.clone().unwrap();
+        let expected = ((0.1 * 180.15588_f64).powi(2) + (2.0 * c6h12o6().mass_uncertainty_amu()).powi(2)).sqrt();
+        assert_near(expected, uncertainty, 1e-9);
+    }
+
+    #[test]
+    fn chem_unit_for_test(){
+        assert_eq!(Ok(ChemUnit { prefix: Some(Prefix::Kilo), base: BaseUnit::Gram }), chem_unit_for(&"kg".to_string()));
+        assert_eq!(Ok(ChemUnit { prefix: Some(Prefix::Micro), base: BaseUnit::Mol }), chem_unit_for(&"µmol".to_string()));
+        assert_eq!(Ok(ChemUnit { prefix: Some(Prefix::Micro), base: BaseUnit::Mol }), chem_unit_for(&"umol".to_string()));
+        assert_eq!(Ok(ChemUnit { prefix: Some(Prefix::Milli), base: BaseUnit::Liter }), chem_unit_for(&"mL".to_string()));
+        assert_eq!(Ok(ChemUnit { prefix: Some(Prefix::Centi), base: BaseUnit::CubicMeter }), chem_unit_for(&"cm³".to_string()));
+        assert_eq!(Ok(ChemUnit { prefix: None, base: BaseUnit::Pound }), chem_unit_for(&"lb".to_string()));
+        assert_eq!(Ok(ChemUnit { prefix: None, base: BaseUnit::Particle }), chem_unit_for(&"molecules".to_string()));
+        for unknown in ["klb", "kparticles", "xg", "gm", ""] {
+            assert_eq!(Err(()), chem_unit_for(&unknown.to_string()), "{}", unknown);
+        }
+    }
+
+    #[test]
+    fn convert_between_compatible_units_test(){
+        let kilograms = ChemQuantity(1.0, chem_unit_for(&"kg".to_string()).unwrap(), 0.0);
+        let ChemQuantity(mmol, _, _) = convert(&c6h12o6(), &kilograms, chem_unit_for(&"mmol".to_string()).unwrap()).unwrap();
+        assert_near(1e6 / 180.15588, mmol, 1e-6);
+        let particles = ChemQuantity(2.0 * 6.022_140_76e23, chem_unit_for(&"particles".to_string()).unwrap(), 0.0);
+        let ChemQuantity(mol, _, _) = convert(&o2(), &particles, ChemUnit::MOL).unwrap();
+        assert_near(2.0, mol, 1e-12);
+        let pounds = ChemQuantity(1.0, chem_unit_for(&"lb".to_string()).unwrap(), 0.0);
+        let ChemQuantity(ounces, _, _) = convert(&o2(), &pounds, chem_unit_for(&"oz".to_string()).unwrap()).unwrap();
+        assert_near(16.0, ounces, 1e-9);
+        let cubic_centimeters = ChemQuantity(250.0, chem_unit_for(&"cm³".to_string()).unwrap(), 0.0);
+        let ChemQuantity(liters, _, _) = convert(&h2o(), &cubic_centimeters, chem_unit_for(&"L".to_string()).unwrap()).unwrap();
+        assert_near(0.25, liters, 1e-12);
+    }
+
+    #[test]
+    fn convert_between_incompatible_units_test(){
+        let liters = ChemQuantity(1.0, chem_unit_for(&"L".to_string()).unwrap(), 0.0);
+        let solid_glucose = Molecule { phase: Some(Phase::Solid), ..c6h12o6() };
+        assert_eq!(
+            Some(ChemError::UnitConversion { from: liters.1, to: ChemUnit::GRAM, species: "C6H12O6(s)".to_string() }),
+            convert(&solid_glucose, &liters, ChemUnit::GRAM).err()
+        );
+        assert_eq!(Dimension::Volume, liters.1.dimension());
+    }
+
+    #[test]
+    fn concentration_for_test(){
+        let Concentration(millimolar, millimolar_unit, _) = concentration_for(100.0, "mM").unwrap();
+        assert_eq!(ConcentrationUnit::Molar, millimolar_unit);
+        assert_near(0.1, millimolar, 1e-12);
+        assert_eq!(ConcentrationUnit::Molar, concentration_for(1.0, "mol/L").unwrap().1);
+        assert_eq!(ConcentrationUnit::GramPerLiter, concentration_for(1.0, "g/L").unwrap().1);
+        assert_eq!(ConcentrationUnit::MassPercent, concentration_for(1.0, "%").unwrap().1);
+        assert!(concentration_for(1.0, "kg/L").is_none());
+    }
+
+    #[test]
+    fn solute_quantity_test(){
+        let milliliters = |value: f64| { ChemQuantity(value, chem_unit_for(&"mL".to_string()).unwrap(), 0.0) };
+        let ChemQuantity(n_mol, unit, _) = Concentration(0.1, ConcentrationUnit::Molar, 0.0).solute_quantity(&milliliters(25.0)).unwrap();
+        assert_eq!(ChemUnit::MOL, unit);
+        assert_near(0.0025, n_mol, 1e-12);
+        let ChemQuantity(grams, unit, _) = Concentration(40.0, ConcentrationUnit::GramPerLiter, 0.0).solute_quantity(&milliliters(250.0)).unwrap();
+        assert_eq!(ChemUnit::GRAM, unit);
+        assert_near(10.0, grams, 1e-12);
+        let kilograms = ChemQuantity(0.5, chem_unit_for(&"kg".to_string()).unwrap(), 0.0);
+        let ChemQuantity(grams, _, _) = Concentration(10.0, ConcentrationUnit::MassPercent, 0.0).solute_quantity(&kilograms).unwrap();
+        assert_near(50.0, grams, 1e-9);
+        assert!(Concentration(10.0, ConcentrationUnit::MassPercent, 0.0).solute_quantity(&milliliters(25.0)).is_none());
+        assert!(Concentration(0.1, ConcentrationUnit::Molar, 0.0).solute_quantity(&kilograms).is_none());
+    }
+
+    #[test]
+    fn molar_concentration_test(){
+        let glucose = ChemQuantity(180.15588, ChemUnit::GRAM, 0.0);
+        let volume = ChemQuantity(500.0, chem_unit_for(&"mL".to_string()).unwrap(), 0.0);
+        let Concentration(molarity, unit, _) = molar_concentration(&c6h12o6(), &glucose, &volume).unwrap();
+        assert_eq!(ConcentrationUnit::Molar, unit);
+        assert_near(2.0, molarity, 1e-9);
+        let mass = ChemQuantity(1.0, ChemUnit::GRAM, 0.0);
+        assert!(molar_concentration(&c6h12o6(), &glucose, &mass).is_err());
+        let empty_volume = ChemQuantity(0.0, ChemUnit::LITER, 0.0);
+        assert!(matches!(molar_concentration(&c6h12o6(), &glucose, &empty_volume), Err(ChemError::QuantityLayout { .. })));
+    }
+
+    #[test]
+    fn convert_gas_volumes_test(){
+        let liters = |value: f64| { ChemQuantity(value, ChemUnit::LITER, 0.0) };
+        assert_near(22.414, GasConditions::STP.molar_volume(), 5e-4);
+        let ChemQuantity(mol, _, _) = convert(&o2(), &liters(22.414), ChemUnit::MOL).unwrap();
+        assert_near(1.0, mol, 1e-4);
+        let room_conditions = GasConditions { temperature: 298.15, pressure: 100_000.0 };
+        let ChemQuantity(volume, _, volume_uncertainty) = convert_at(&co2(), &ChemQuantity(44.0095, ChemUnit::GRAM, 0.0), ChemUnit::LITER, &room_conditions).unwrap();
+        assert_near(8.314_462_618 * 298.15 / 100.0, volume, 1e-4);
+        assert!(volume_uncertainty > 0.0);
+        let liquid_water = Molecule { phase: Some(Phase::Liquid), ..h2o() };
+        assert_eq!(
+            Some(ChemError::UnitConversion { from: ChemUnit::LITER, to: ChemUnit::MOL, species: "H2O(l)".to_string() }),
+            convert(&liquid_water, &liters(1.0), ChemUnit::MOL).err()
+        );
+    }
+
+    #[test]
+    fn format_with_uncertainty_test(){
+        assert_eq!("18.01528 ± 0.00033", format_with_uncertainty(18.015_28, 0.000_331));
+        assert_eq!("306.2650 ± 0.0088", format_with_uncertainty(306.265, 0.008_8));
+        assert_eq!("98.000", format_with_uncertainty(98.0, 0.0));
+        assert_eq!("0.500", format_with_uncertainty(0.5, 0.0));
+        assert_eq!("0.0025", format_with_uncertainty(2.5e-3, 0.0));
+        assert_eq!("5e-6", format_with_uncertainty(5e-6, 0.0));
+        assert_eq!("3.32108e-24", format_with_uncertainty(2.0 / 6.02214076e23, 0.0));
+        assert_eq!("(6.69489 ± 0.00046)e-24", format_with_uncertainty(6.694_886e-24, 4.6e-28));
+        assert_eq!("(1.2 ± 3.0)e-4", format_with_uncertainty(1.2e-4, 3e-4));
+        assert_eq!("6.02214e23", format_with_uncertainty(6.02214076e23, 0.0));
+        assert_eq!("0.99875", format_with_uncertainty(0.99875, 0.0));
+        assert_eq!("0.333333", format_with_uncertainty(1.0 / 3.0, 0.0));
+        assert_eq!("0.000", format_with_uncertainty(0.0, 0.0));
     }
 
     #[test]
@@ -45,6 +186,109 @@ mod chemistry_tests {
         assert_eq!(expected_balanced_equation, balanced_equation_res.unwrap());
     }
 
+    #[test]
+    fn balance_all_underconstrained_eq_test(){
+        let h2o2 = Molecule {
+            atoms: BTreeMap::from([(test_atoms::hydrogen(), 2), (test_atoms::oxygen(), 2)]),
+            charge: 0,
+            string_repr: Some("H2O2".to_string()),
+            phase: None
+        };
+        let h2 = Molecule {
+            atoms: BTreeMap::from([(test_atoms::hydrogen(), 2)]),
+            charge: 0,
+            string_repr: Some("H2".to_string()),
+            phase: None
+        };
+        let raw_equation = RawEquation {
+            lhs: Vec::from([ h2o2.clone() ]),
+            rhs: Vec::from([ h2o(), o2(), h2.clone() ]),
+            arrow: "=>".to_string()
+        };
+        assert_eq!(Err(ChemError::Underconstrained { n_independent_reactions: 2 }), balance(&raw_equation));
+        let expected_equations = Vec::from([
+            BalancedEquation {
+                lhs: Vec::from([ (h2o2.clone(), 2) ]),
+                rhs: Vec::from([ (h2o(), 2), (o2(), 1) ]),
+                arrow: "=>".to_string()
+            },
+            BalancedEquation {
+                lhs: Vec::from([ (h2o2, 1), (h2, 1) ]),
+                rhs: Vec::from([ (h2o(), 2) ]),
+                arrow: "=>".to_string()
+            }
+        ]);
+        let balanced_equations_res = balance_all(&raw_equation);
+        assert!(balanced_equations_res.is_ok());
+        assert_eq!(expected_equations, balanced_equations_res.unwrap());
+    }
+
+    #[test]
+    fn balance_with_minimal_coefs_sum_test(){
+        let h2o2 = Molecule {
+            atoms: BTreeMap::from([(test_atoms::hydrogen(), 2), (test_atoms::oxygen(), 2)]),
+            charge: 0,
+            string_repr: Some("H2O2".to_string()),
+            phase: None
+        };
+        let h2 = Molecule {
+            atoms: BTreeMap::from([(test_atoms::hydrogen(), 2)]),
+            charge: 0,
+            string_repr: Some("H2".to_string()),
+            phase: None
+        };
+        let raw_equation = RawEquation {
+            lhs: Vec::from([ h2o2.clone() ]),
+            rhs: Vec::from([ h2o(), o2(), h2.clone() ]),
+            arrow: "=>".to_string()
+        };
+        let expected_balanced_equation = BalancedEquation {
+            lhs: Vec::from([ (h2o2, 3) ]),
+            rhs: Vec::from([ (h2o(), 2), (o2(), 2), (h2, 1) ]),
+            arrow: "=>".to_string()
+        };
+        let balancing_res = balance_with(&raw_equation, BalancingCriterion::MinimalCoefficientsSum);
+        assert!(balancing_res.is_ok());
+        let balancing = balancing_res.unwrap();
+        assert!(balancing.chosen_among_several);
+        assert_eq!(expected_balanced_equation, balancing.equation);
+        assert!(balance_with(&raw_equation, BalancingCriterion::Unique).is_err());
+    }
+
+    #[test]
+    fn balance_with_minimal_coefs_sum_unique_balancing_test(){
+        let raw_equation = RawEquation {
+            lhs: Vec::from([ h2o(), co2() ]),
+            rhs: Vec::from([ c6h12o6(), o2() ]),
+            arrow: "=>".to_string()
+        };
+        let balancing_res = balance_with(&raw_equation, BalancingCriterion::MinimalCoefficientsSum);
+        assert!(balancing_res.is_ok());
+        let balancing = balancing_res.unwrap();
+        assert!(!balancing.chosen_among_several);
+        assert_eq!(balance(&raw_equation).unwrap(), balancing.equation);
+    }
+
+    #[test]
+    fn element_only_in_products_test(){
+        let h2 = Molecule {
+            atoms: BTreeMap::from([(test_atoms::hydrogen(), 2)]),
+            charge: 0,
+            string_repr: Some("H2".to_string()),
+            phase: None
+        };
+        let raw_equation = RawEquation {
+            lhs: Vec::from([ h2 ]),
+            rhs: Vec::from([ h2o() ]),
+            arrow: "=>".to_string()
+        };
+        let expected_unconserved = Vec::from([
+            UnconservedElement { atom: test_atoms::oxygen(), side: EquationSide::Products, position: None }
+        ]);
+        assert_eq!(expected_unconserved, unconserved_elements(&raw_equation));
+        assert!(balance(&raw_equation).is_err());
+    }
+
     #[test]
     fn compute_rhs_test(){
 
@@ -58,8 +302,8 @@ mod chemistry_tests {
 
         let eq = QuantifiedEquation {
             lhs: Vec::from([
-                (c6h12o6(), Some(ChemQuantity(180_160.0 * 9.0, Milligram))),
-                (o2(), Some(ChemQuantity(32.0*6.0*7.0, Gram)))
+                (c6h12o6(), Some(ChemQuantity(180_160.0 * 9.0, ChemUnit::MILLIGRAM, 0.0))),
+                (o2(), Some(ChemQuantity(32.0*6.0*7.0, ChemUnit::GRAM, 0.0)))
             ]),
             rhs: Vec::from([
                 (h2o(), None),
@@ -69,19 +313,20 @@ mod chemistry_tests {
         };
         let expected_eq = QuantifiedEquation {
             lhs: Vec::from([
-                (c6h12o6(), Some(ChemQuantity(9.0, Mol))),
-                (o2(), Some(ChemQuantity(6.0*7.0, Mol)))
+                (c6h12o6(), Some(ChemQuantity(9.0, ChemUnit::MOL, 0.0))),
+                (o2(), Some(ChemQuantity(6.0*7.0, ChemUnit::MOL, 0.0)))
             ]),
             rhs: Vec::from([
-                (h2o(), Some(ChemQuantity(6.0*7.0, Mol))),
-                (co2(), Some(ChemQuantity(6.0*7.0, Mol)))
+                (h2o(), Some(ChemQuantity(6.0*7.0, ChemUnit::MOL, 0.0))),
+                (co2(), Some(ChemQuantity(6.0*7.0, ChemUnit::MOL, 0.0)))
             ]),
             arrow: "=>".to_string()
         };
-        let act_res = compute_lhs_coefs(&eq);
+        let act_res = compute_lhs_coefs(&eq, &GasConditions::STP);
         assert!(act_res.is_ok());
-        let (act_eq, act_limiting) = act_res.unwrap();
-        assert_eq!(o2(), act_limiting);
+        let quantities = act_res.unwrap();
+        let (act_eq, act_limiting) = (quantities.equation, quantities.limiting_reactant);
+        assert_eq!(Some(o2()), act_limiting);
         let expected_member = expected_eq.lhs;
         let actual_member = act_eq.lhs;
         let zipped: Vec<(&(Molecule, Option<ChemQuantity>), (Molecule, Option<ChemQuantity>))> =
@@ -96,6 +341,104 @@ mod chemistry_tests {
         }
     }
 
+    #[test]
+    fn compute_excess_reactants_test(){
+        // 9 mol of C6H12O6 would need 54 mol of O2, 42 mol of O2 only react with 7 mol of C6H12O6
+        let eq = QuantifiedEquation {
+            lhs: Vec::from([
+                (c6h12o6(), Some(ChemQuantity(9.0, ChemUnit::MOL, 0.0))),
+                (o2(), Some(ChemQuantity(6.0*7.0, ChemUnit::MOL, 0.0)))
+            ]),
+            rhs: Vec::from([
+                (h2o(), None),
+                (co2(), None)
+            ]),
+            arrow: "=>".to_string()
+        };
+        let quantities = compute_lhs_coefs(&eq, &GasConditions::STP).unwrap();
+        assert_eq!(1, quantities.excess_reactants.len());
+        let excess = &quantities.excess_reactants[0];
+        assert_eq!(c6h12o6(), excess.molecule);
+        assert_eq!(ChemUnit::MOL, excess.consumed.1);
+        assert_near(7.0, excess.consumed.0, 1e-9);
+        assert_eq!(ChemUnit::MOL, excess.leftover.1);
+        assert_near(2.0, excess.leftover.0, 1e-9);
+    }
+
+    #[test]
+    fn compute_reactants_from_product_test(){
+        // 42 mol of H2O need 7 mol of C6H12O6 and 42 mol of O2, and also give 42 mol of CO2
+        let eq = QuantifiedEquation {
+            lhs: Vec::from([ (c6h12o6(), None), (o2(), None) ]),
+            rhs: Vec::from([ (h2o(), Some(ChemQuantity(6.0*7.0, ChemUnit::MOL, 0.0))), (co2(), None) ]),
+            arrow: "=>".to_string()
+        };
+        let quantities = compute_lhs_coefs(&eq, &GasConditions::STP).unwrap();
+        assert_eq!(None, quantities.limiting_reactant);
+        assert!(quantities.excess_reactants.is_empty());
+        let n_mol: Vec<f64> = quantities.equation.lhs.iter().chain(&quantities.equation.rhs)
+            .map(|(_, qty)| { qty.clone().unwrap().0 })
+            .collect();
+        for (expected, actual) in [7.0, 42.0, 42.0, 42.0].into_iter().zip(n_mol) {
+            assert_near(expected, actual, 1e-9);
+        }
+    }
+
+    #[test]
+    fn compute_with_reactant_and_product_quantities_test(){
+        let eq = QuantifiedEquation {
+            lhs: Vec::from([ (c6h12o6(), Some(ChemQuantity(1.0, ChemUnit::MOL, 0.0))), (o2(), None) ]),
+            rhs: Vec::from([ (h2o(), Some(ChemQuantity(6.0, ChemUnit::MOL, 0.0))), (co2(), None) ]),
+            arrow: "=>".to_string()
+        };
+        assert!(matches!(compute_lhs_coefs(&eq, &GasConditions::STP), Err(ChemError::QuantityLayout { .. })));
+    }
+
+    #[test]
+    fn pure_and_gross_quantities_test(){
+        let pure = pure_quantity(&ChemQuantity(100.0, ChemUnit::GRAM, 0.5), 0.92).unwrap();
+        assert_near(92.0, pure.0, 1e-9);
+        assert_near(0.46, pure.2, 1e-9);
+        let gross = gross_quantity(&pure, 0.92).unwrap();
+        assert_near(100.0, gross.0, 1e-9);
+        assert_near(0.5, gross.2, 1e-9);
+        assert!(pure_quantity(&ChemQuantity(1.0, ChemUnit::MOL, 0.0), 0.92).is_none());
+    }
+
+    #[test]
+    fn compute_yields_test(){
+        // 1 mol of C6H12O6 gives at most 6 mol of H2O and 6 mol of CO2
+        let eq = QuantifiedEquation {
+            lhs: Vec::from([ (c6h12o6(), Some(ChemQuantity(1.0, ChemUnit::MOL, 0.0))), (o2(), Some(ChemQuantity(10.0, ChemUnit::MOL, 0.0))) ]),
+            rhs: Vec::from([ (h2o(), Some(ChemQuantity(4.5, ChemUnit::MOL, 0.0))), (co2(), Some(ChemQuantity(5.4, ChemUnit::MOL, 0.0))) ]),
+            arrow: "=>".to_string()
+        };
+        let report = compute_yields(&eq, &[0, 1], &GasConditions::STP).unwrap();
+        assert_eq!(Some(c6h12o6()), report.quantities.limiting_reactant);
+        assert_eq!(2, report.yields.len());
+        assert_eq!(h2o(), report.yields[0].product);
+        assert_near(6.0, report.yields[0].theoretical.0, 1e-9);
+        assert_near(4.5, report.yields[0].actual.0, 1e-9);
+        assert_near(75.0, report.yields[0].percent_yield, 1e-9);
+        assert_near(90.0, report.yields[1].percent_yield, 1e-9);
+        assert_near(82.5, report.efficiency, 1e-9);
+        assert!(!report.yields[0].exceeds_theoretical());
+        let eq_above_theoretical = QuantifiedEquation {
+            rhs: Vec::from([ (h2o(), Some(ChemQuantity(6.6, ChemUnit::MOL, 0.0))), (co2(), None) ]),
+            ..eq.clone()
+        };
+        let report = compute_yields(&eq_above_theoretical, &[0], &GasConditions::STP).unwrap();
+        assert_near(110.0, report.yields[0].percent_yield, 1e-9);
+        assert!(report.yields[0].exceeds_theoretical());
+        let eq_without_reactant = QuantifiedEquation {
+            lhs: Vec::from([ (c6h12o6(), Some(ChemQuantity(0.0, ChemUnit::MOL, 0.0))), (o2(), Some(ChemQuantity(10.0, ChemUnit::MOL, 0.0))) ]),
+            ..eq.clone()
+        };
+        let error = compute_yields(&eq_without_reactant, &[0, 1], &GasConditions::STP).err().unwrap();
+        assert_eq!("the given quantities cannot give any H2O, so its yield is undefined", error.to_string());
+        assert!(matches!(compute_yields(&eq, &[], &GasConditions::STP), Err(ChemError::QuantityLayout { .. })));
+    }
+
     fn assert_near(expected: f64, actual: f64, margin: f64){
         let ok = (expected - actual).abs() <= margin;
         if !ok {
@@ -2,18 +2,119 @@
 #[cfg(test)]
 mod data_loading_tests {
     use std::fs;
-    use Stoichio_calc::data_loading::load_peridic_table_as_vec;
+    use Stoichio_calc::chemistry::Phase;
+    use Stoichio_calc::data_loading::{load_elements_properties, load_peridic_table_as_vec, load_periodic_table};
+    use Stoichio_calc::error::LoadError;
+
+    const HEADER: &str = "AtomicNumber,Element,Symbol,AtomicMass,Phase";
 
     #[test]
     fn load_periodic_table_tests(){
         let loaded = load_peridic_table_as_vec(
             fs::read_to_string("./res/periodic_table.csv").unwrap().as_str()
-        );
+        ).unwrap();
         assert_eq!(118, loaded.len());
         assert_eq!("Neon", loaded.get(9).unwrap().name);
         assert_eq!("Se", loaded.get(33).unwrap().code);
-        assert_eq!(174_967, loaded.get(70).unwrap().atomic_mass_milli_amu);
+        assert_eq!(174_966_800_000, loaded.get(70).unwrap().atomic_mass_nano_amu);
     }
 
-}
+    #[test]
+    fn load_elements_properties_test(){
+        let loaded = load_elements_properties(
+            fs::read_to_string("./res/periodic_table.csv").unwrap().as_str()
+        ).unwrap();
+        assert_eq!(118, loaded.len());
+        let hydrogen = &loaded[0];
+        assert_eq!("H", hydrogen.atom.code);
+        assert_eq!(1, hydrogen.atomic_number);
+        assert_eq!(Some(1), hydrogen.group);
+        assert_eq!(Some(Phase::Gas), hydrogen.phase);
+        assert_eq!(Some(8.99E-05), hydrogen.density);
+        assert_eq!(Some(2.2), hydrogen.electronegativity);
+        assert!(hydrogen.natural && hydrogen.nonmetal && !hydrogen.metal);
+        assert_eq!(Some("Cavendish".to_string()), hydrogen.discoverer);
+        assert_eq!(Some(1766), hydrogen.discovery_year);
+        let helium = &loaded[1];
+        assert_eq!(None, helium.electronegativity);
+        assert_eq!(None, helium.n_valence_electrons);
+        let manganese = &loaded[24];
+        assert_eq!(Some("Gahn, Scheele".to_string()), manganese.discoverer);
+        assert_eq!(Some(1774), manganese.discovery_year);
+        let meitnerium = &loaded[108];
+        assert_eq!(None, meitnerium.phase);
+        assert_eq!(Some(7), meitnerium.n_shells);
+    }
+
+
+    #[test]
+    fn load_minimal_columns_test(){
+        let loaded = load_periodic_table(&format!("{}\n1,Hydrogen,H,1.00794,gas\n2,Helium,He,4.002602,gas\n", HEADER)).unwrap();
+        assert_eq!(2, loaded.len());
+        assert_eq!("Helium", loaded.get("He").unwrap().name);
+        assert_eq!(1_007_940_000, loaded.get("H").unwrap().atomic_mass_nano_amu);
+        assert_eq!(4_002_602_000, loaded.get("He").unwrap().atomic_mass_nano_amu);
+    }
+
+    #[test]
+    fn load_mass_uncertainties_test(){
+        let loaded = load_periodic_table(
+            &format!("{}\n1,Hydrogen,H,1.00794(7),gas\n5,Boron,B,\"[10.806, 10.821]\",solid\n6,Carbon,C,[12.0096;12.0116],solid", HEADER)
+        ).unwrap();
+        let hydrogen = loaded.get("H").unwrap();
+        assert_eq!((1_007_940_000, 70_000), (hydrogen.atomic_mass_nano_amu, hydrogen.atomic_mass_uncertainty_nano_amu));
+        let boron = loaded.get("B").unwrap();
+        assert_eq!((10_813_500_000, 7_500_000), (boron.atomic_mass_nano_amu, boron.atomic_mass_uncertainty_nano_amu));
+        let carbon = loaded.get("C").unwrap();
+        assert_eq!((12_010_600_000, 1_000_000), (carbon.atomic_mass_nano_amu, carbon.atomic_mass_uncertainty_nano_amu));
+    }
+
+    #[test]
+    fn invalid_interval_error_test(){
+        assert_eq!(
+            Err(LoadError::InvalidCell { line: 2, column: 4, column_name: "AtomicMass".to_string(), cell: "[10.821;10.806]".to_string() }),
+            load_periodic_table(&format!("{}\n5,Boron,B,[10.821;10.806],solid", HEADER))
+        );
+    }
 
+    #[test]
+    fn invalid_mass_error_test(){
+        assert_eq!(
+            Err(LoadError::InvalidCell { line: 3, column: 4, column_name: "AtomicMass".to_string(), cell: "4.0o2".to_string() }),
+            load_periodic_table(&format!("{}\n1,Hydrogen,H,1.00794,gas\n2,Helium,He,4.0o2,gas", HEADER))
+        );
+    }
+
+    #[test]
+    fn missing_cell_error_test(){
+        assert_eq!(
+            Err(LoadError::ColumnCount { line: 2, expected: 5, found: 4 }),
+            load_periodic_table(&format!("{}\n1,Hydrogen,H,1.00794", HEADER))
+        );
+    }
+
+    #[test]
+    fn duplicate_symbol_error_test(){
+        assert_eq!(
+            Err(LoadError::DuplicateSymbol { line: 3, symbol: "H".to_string(), first_line: 2 }),
+            load_periodic_table(&format!("{}\n1,Hydrogen,H,1.00794,gas\n2,Helium,H,4.002602,gas", HEADER))
+        );
+    }
+
+    #[test]
+    fn duplicate_atomic_number_error_test(){
+        assert_eq!(
+            Err(LoadError::DuplicateAtomicNumber { line: 3, atomic_number: 1, first_line: 2 }),
+            load_periodic_table(&format!("{}\n1,Hydrogen,H,1.00794,gas\n1,Helium,He,4.002602,gas", HEADER))
+        );
+    }
+
+    #[test]
+    fn missing_column_error_test(){
+        assert_eq!(
+            Err(LoadError::MissingColumn { column_name: "AtomicMass".to_string() }),
+            load_periodic_table("AtomicNumber,Element,Symbol\n1,Hydrogen,H")
+        );
+    }
+
+}
@@ -0,0 +1,50 @@
+use std::fs;
+use Stoichio_calc::data_loading::load_elements_properties;
+use Stoichio_calc::elements::{Comparison, ElementFilter, ElementProperties, filter_elements, find_element, parse_element_filters};
+use Stoichio_calc::error::ChemError;
+
+fn load_elements() -> Vec<ElementProperties> {
+    load_elements_properties(fs::read_to_string("./res/periodic_table.csv").unwrap().as_str()).unwrap()
+}
+
+#[test]
+fn find_element_by_symbol_name_or_number_test(){
+    let elements = load_elements();
+    assert_eq!("Fe", find_element(&elements, "Fe").unwrap().atom.code);
+    assert_eq!("Fe", find_element(&elements, "iron").unwrap().atom.code);
+    assert_eq!("Fe", find_element(&elements, "26").unwrap().atom.code);
+    assert_eq!(None, find_element(&elements, "Xx"));
+}
+
+#[test]
+fn parse_element_filters_test(){
+    let expected = vec![
+        ElementFilter { property: "group".to_string(), comparison: Comparison::Equal, value: "17".to_string() },
+        ElementFilter { property: "electronegativity".to_string(), comparison: Comparison::GreaterOrEqual, value: "3".to_string() },
+    ];
+    assert_eq!(Ok(expected), parse_element_filters("group=17 and electronegativity >= 3"));
+}
+
+#[test]
+fn unknown_property_filter_test(){
+    assert_eq!(
+        Err(ChemError::UnknownElementProperty { property: "colour".to_string(), span: 14..20 }),
+        parse_element_filters("phase=gas and colour=red")
+    );
+}
+
+#[test]
+fn filter_halogens_test(){
+    let elements = load_elements();
+    let filters = parse_element_filters("group=17").unwrap();
+    let codes: Vec<String> = filter_elements(&elements, &filters).iter().map(|elem| { elem.atom.code.clone() }).collect();
+    assert_eq!(vec!["F", "Cl", "Br", "I", "At", "Ts"], codes);
+}
+
+#[test]
+fn filter_gases_by_electronegativity_test(){
+    let elements = load_elements();
+    let filters = parse_element_filters("phase=gas and electronegativity>3").unwrap();
+    let codes: Vec<String> = filter_elements(&elements, &filters).iter().map(|elem| { elem.atom.code.clone() }).collect();
+    assert_eq!(vec!["N", "O", "F", "Cl"], codes);
+}
@@ -5,9 +5,11 @@
 
 mod end_to_end_tests {
     use std::fs;
-    use Stoichio_calc::chemistry::balance;
+    use Stoichio_calc::chemistry::{balance, balance_in_medium, balance_with, BalancingCriterion, compute_lhs_coefs, GasConditions, Medium, PeriodicTable, products_concentrations};
+    use Stoichio_calc::chemistry::Medium::{Acidic, Basic};
+    use Stoichio_calc::error::ChemError;
     use Stoichio_calc::data_loading::load_periodic_table;
-    use Stoichio_calc::parsing::{parse_raw_equation, tokenize};
+    use Stoichio_calc::parsing::{parse_molecule, parse_quantified_equation, parse_quantity, parse_raw_equation, tokenize};
 
     macro_rules! equation_balancing_test {
         ($input: literal, $expected: literal, $name: ident) => {
@@ -36,10 +38,163 @@ mod end_to_end_tests {
 
     equation_balancing_test!("O^-2 + F2 => O2 + F^-", "2 O^-2 + 2 F2 => O2 + 4 F^-", o2minus2_x_f2_test);
 
+    // half-reactions, with electrons
+
+    equation_balancing_test!("MnO4^- + H^+ + e^- => Mn^2+ + H2O", "MnO4^- + 8 H^+ + 5 e^- => Mn^2+ + 4 H2O", mno4minus_half_reaction_test);
+
+    equation_balancing_test!("Cr2O7^2- + H^+ + e- => Cr^3+ + H2O", "Cr2O7^2- + 14 H^+ + 6 e- => 2 Cr^3+ + 7 H2O", cr2o7_2minus_half_reaction_test);
+
+    equation_balancing_test!("Cu => Cu^2+ + e^-", "Cu => Cu^2+ + 2 e^-", cu_half_reaction_test);
+
+    equation_balancing_test!("Fe^2+ => Fe^3+ + e^-", "Fe^2+ => Fe^3+ + e^-", fe2plus_half_reaction_test);
+
+    // equations whose elimination used to overflow i32
+
+    equation_balancing_test!("K4Fe(CN)6 + KMnO4 + H2SO4 => KHSO4 + Fe2(SO4)3 + MnSO4 + HNO3 + CO2 + H2O",
+        "10 K4Fe(CN)6 + 122 KMnO4 + 299 H2SO4 => 162 KHSO4 + 5 Fe2(SO4)3 + 122 MnSO4 + 60 HNO3 + 60 CO2 + 188 H2O",
+        k4fe_cn6_x_kmno4_x_h2so4_test);
+
+    equation_balancing_test!("[Cr(N2H4CO)6]4[Cr(CN)6]3 + KMnO4 + H2SO4 => K2Cr2O7 + MnSO4 + CO2 + KNO3 + K2SO4 + H2O",
+        "10 [Cr(N2H4CO)6]4[Cr(CN)6]3 + 1176 KMnO4 + 1399 H2SO4 => 35 K2Cr2O7 + 1176 MnSO4 + 420 CO2 + 660 KNO3 + 223 K2SO4 + 1879 H2O",
+        cr_n2h4co_6_4_cr_cn_6_3_x_kmno4_x_h2so4_test);
+
+    equation_balancing_test!("Fe3517O4999 + Al7013 => Fe + Al2O3",
+        "21039 Fe3517O4999 + 9998 Al7013 => 73994163 Fe + 35057987 Al2O3",
+        fe3517o4999_x_al7013_test);
+
+    equation_balancing_test!("C3011H5027O1013N997 + HNO3 => CO2 + H2O + NO",
+        "3 C3011H5027O1013N997 + 17039 HNO3 => 9033 CO2 + 16060 H2O + 20030 NO",
+        c3011h5027o1013n997_x_hno3_test);
+
+    // equation with 5 independent balancings, none of them positive, whose search for a minimal balancing used to try
+    // all the combinations up to the largest sum of coefficients
+
+    #[test]
+    fn fe_ions_no_positive_balancing_test() {
+        let raw_eq = parse_raw_equation(&periodic_table(), &tokenize(&"Fe^2+ => Fe^3+ + Fe^4+ + Fe^5+ + Fe^6+ + Fe^7+ + Fe^8+".to_string())).unwrap();
+        assert_eq!(
+            Err(ChemError::NoPositiveBalancing { max_coefs_sum: None }),
+            balance_with(&raw_eq, BalancingCriterion::MinimalCoefficientsSum)
+        );
+    }
+
+    macro_rules! medium_balancing_test {
+        ($input: literal, $medium: expr, $expected: literal, $name: ident) => {
+            #[test]
+            fn $name() {
+                perform_medium_balancing_test($input, $medium, $expected);
+            }
+        };
+    }
+
+    // skeletal redox equations, completed with the species of the medium
+
+    medium_balancing_test!("MnO4^- + Fe^2+ => Mn^2+ + Fe^3+", Acidic,
+        "MnO4^- + 5 Fe^2+ + 8 H^+ => Mn^2+ + 5 Fe^3+ + 4 H2O", mno4minus_x_fe2plus_acidic_test);
+
+    medium_balancing_test!("MnO4^- + Fe^2+ => Mn^2+ + Fe^3+", Basic,
+        "MnO4^- + 5 Fe^2+ + 4 H2O => Mn^2+ + 5 Fe^3+ + 8 OH^-", mno4minus_x_fe2plus_basic_test);
+
+    medium_balancing_test!("Cr2O7^2- + I^- => Cr^3+ + I2", Acidic,
+        "Cr2O7^2- + 6 I^- + 14 H^+ => 2 Cr^3+ + 3 I2 + 7 H2O", cr2o7_2minus_x_iminus_acidic_test);
+
+    medium_balancing_test!("Cu + NO3^- => Cu^2+ + NO", Acidic,
+        "3 Cu + 2 NO3^- + 8 H^+ => 3 Cu^2+ + 2 NO + 4 H2O", cu_x_no3minus_acidic_test);
+
+    medium_balancing_test!("Cl2 => Cl^- + ClO3^-", Basic,
+        "3 Cl2 + 6 OH^- => 5 Cl^- + ClO3^- + 3 H2O", cl2_disproportionation_basic_test);
+
+    medium_balancing_test!("MnO4^- + I^- + H2O => MnO2 + I2", Basic,
+        "2 MnO4^- + 6 I^- + 4 H2O => 2 MnO2 + 3 I2 + 8 OH^-", mno4minus_x_iminus_with_water_basic_test);
+
+    #[test]
+    fn medium_balancing_without_electron_donor_test() {
+        let raw_eq = parse_raw_equation(&periodic_table(), &tokenize(&"MnO4^- => Mn^2+".to_string())).unwrap();
+        assert_eq!(Err(ChemError::Inconsistent), balance_in_medium(&raw_eq, Acidic, &periodic_table()));
+    }
+
+    macro_rules! compute_test {
+        ($input: literal, $expected: literal, $name: ident) => {
+            #[test]
+            fn $name() {
+                perform_compute_test($input, $expected);
+            }
+        };
+    }
+
+    // the amounts in mol must keep the significant digits of small exact quantities, down to counts of particles
+
+    compute_test!("5 µmol H2 + 1 mol O2 => H2O", "5e-6 mol H2 + 1.000 mol O2 => 5e-6 mol H2O", micromoles_compute_test);
+
+    compute_test!("2 molecules H2 + 1 mol O2 => H2O", "3.32108e-24 mol H2 + 1.000 mol O2 => 3.32108e-24 mol H2O", particles_compute_test);
+
+    compute_test!("25 mL 0.1 M HCl + 1 mol NaOH => NaCl + H2O",
+        "0.0025 mol HCl + 1.000 mol NaOH => 0.0025 mol NaCl + 0.0025 mol H2O", molarity_compute_test);
+
+    compute_test!("2.5e-3 mol H2 + 1 mol O2 => H2O", "0.0025 mol H2 + 1.000 mol O2 => 0.0025 mol H2O", scientific_notation_compute_test);
+
+    #[test]
+    fn solvent_has_no_concentration_test() {
+        let quant_eq = parse_quantified_equation(&periodic_table(), &tokenize(&"1 mol NaOH + 1 mol HCl => NaCl + H2O".to_string())).unwrap();
+        let quantities = compute_lhs_coefs(&quant_eq, &GasConditions::STP).unwrap();
+        let volume = parse_quantity(&tokenize(&"500 mL".to_string())).unwrap();
+        let concentrations: Vec<String> = products_concentrations(&quantities.equation, &volume).unwrap().iter()
+            .map(|(molec, conc)| { format!("{} {}", conc, molec) })
+            .collect();
+        assert_eq!(vec!["2.000 M NaCl".to_string()], concentrations);
+    }
+
+    macro_rules! molar_mass_test {
+        ($input: literal, $expected: literal, $name: ident) => {
+            #[test]
+            fn $name() {
+                perform_molar_mass_test($input, $expected);
+            }
+        };
+    }
+
+    // reference molar masses computed from the IUPAC standard atomic weights
+
+    molar_mass_test!("H2O", 18.0153, h2o_molar_mass_test);
+
+    molar_mass_test!("C6H12O6", 180.1559, c6h12o6_molar_mass_test);
+
+    molar_mass_test!("NaCl", 58.4428, nacl_molar_mass_test);
+
+    molar_mass_test!("CuSO4", 159.6086, cuso4_molar_mass_test);
+
+    molar_mass_test!("C3011H5027O1013N997", 71403.2042, c3011h5027o1013n997_molar_mass_test);
+
+    fn perform_molar_mass_test(input_molecule: &str, expected_mass: f64) {
+        let molecule = parse_molecule(
+            &load_periodic_table(fs::read_to_string("./res/periodic_table.csv").unwrap().as_str()).unwrap(),
+            &tokenize(&input_molecule.to_string()),
+        ).unwrap();
+        let margin = 0.00005;
+        let actual_mass = molecule.mass_amu();
+        assert!((expected_mass - actual_mass).abs() <= margin, "expected {}, was {}", expected_mass, actual_mass);
+    }
+
+    fn perform_compute_test(input_eq: &str, expected_mol_eq: &str) {
+        let quant_eq = parse_quantified_equation(&periodic_table(), &tokenize(&input_eq.to_string())).unwrap();
+        let quantities = compute_lhs_coefs(&quant_eq, &GasConditions::STP).unwrap();
+        assert_eq!(expected_mol_eq.to_string(), quantities.equation.to_string())
+    }
+
+    fn perform_medium_balancing_test(input_eq: &str, medium: Medium, expected_output_eq: &str) {
+        let raw_eq = parse_raw_equation(&periodic_table(), &tokenize(&input_eq.to_string())).unwrap();
+        let balanced_eq = balance_in_medium(&raw_eq, medium, &periodic_table()).unwrap();
+        assert_eq!(expected_output_eq.to_string(), balanced_eq.to_string())
+    }
+
+    fn periodic_table() -> PeriodicTable {
+        load_periodic_table(fs::read_to_string("./res/periodic_table.csv").unwrap().as_str()).unwrap()
+    }
+
     fn perform_equation_balancing_test(input_eq: &str, expected_output_eq: &str) {
         let parsed_raw_eq =
             parse_raw_equation(
-                &load_periodic_table(fs::read_to_string("./res/periodic_table.csv").unwrap().as_str()),
+                &load_periodic_table(fs::read_to_string("./res/periodic_table.csv").unwrap().as_str()).unwrap(),
                 &tokenize(&input_eq.to_string()),
             );
         assert!(parsed_raw_eq.is_ok());
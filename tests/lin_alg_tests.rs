@@ -1,4 +1,4 @@
-use Stoichio_calc::lin_alg::Matrix;
+use Stoichio_calc::lin_alg::{has_positive_combination, Matrix};
 
 #[test]
 fn diagonalize_3x4_test(){
@@ -69,3 +69,29 @@ fn diagonalize_5x4_test(){
     assert!(actual_output.is_ok());
     assert_eq!(expected_output, actual_output.unwrap());
 }
+
+#[test]
+fn diagonalize_large_coefs_test(){
+    // eliminating the first column multiplies 65521 by 65497, which exceeds i32::MAX
+    let input = Matrix::of_arr(&[
+        &[65521, 65519, 1],
+        &[65497, 65479, -1]
+    ]);
+    let expected_output = Matrix::of_arr(&[
+        &[524192, 0, -65499],
+        &[0, 524192, 65509]
+    ]);
+    let actual_output = input.diagonalized();
+    assert!(actual_output.is_ok());
+    assert_eq!(expected_output, actual_output.unwrap());
+}
+
+#[test]
+fn has_positive_combination_test(){
+    // (1, 0, -1) + 2 (0, 1, 1) = (1, 2, 1)
+    assert!(has_positive_combination(&Matrix::of_arr(&[ &[1, 0, -1], &[0, 1, 1] ]).coefs()));
+    // the last coefficient is negative as soon as the first two are positive
+    assert!(!has_positive_combination(&Matrix::of_arr(&[ &[1, 0, -1], &[0, 1, -1] ]).coefs()));
+    assert!(has_positive_combination(&Matrix::of_arr(&[ &[-2, -1, -3] ]).coefs()));
+    assert!(!has_positive_combination(&Matrix::of_arr(&[ &[1, 0, 2] ]).coefs()));
+}
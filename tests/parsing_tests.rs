@@ -7,10 +7,11 @@ mod test_molecules;
 #[cfg(test)]
 mod parsing_tests {
     use std::collections::btree_map::BTreeMap;
-    use Stoichio_calc::chemistry::{Atom, ChemQuantity, Molecule, QuantifiedEquation, RawEquation};
-    use Stoichio_calc::chemistry::ChemUnit::{Gram, Mol};
+    use Stoichio_calc::chemistry::{Atom, ChemQuantity, compute_lhs_coefs, convert, EquationSide, GasConditions, Molecule, Phase, QuantifiedEquation, RawEquation, unconserved_elements, UnconservedElement};
+    use Stoichio_calc::chemistry::ChemUnit;
+    use Stoichio_calc::error::ChemError;
 
-    use Stoichio_calc::parsing::{parse_molecule, parse_quantified_equation, parse_raw_equation, Token, tokenize, TokenType};
+    use Stoichio_calc::parsing::{locate_unconserved_elements, parse_molecule, parse_gas_conditions, parse_annotated_equation, parse_numeric_literal, parse_quantified_equation, parse_quantity, parse_raw_equation, split_reaction_conditions, Token, tokenize, TokenType};
     use crate::{assert_near, test_atoms};
     use crate::test_molecules::{c6h12o6, co2, h2o, o2};
 
@@ -59,6 +60,19 @@ mod parsing_tests {
         expect_molecule_parsing_failure("Se[Ch3O")
     }
 
+    #[test]
+    fn unknown_element_error_test() {
+        let result = parse_molecule(&test_atoms::atoms_map(), &tokenize(&"NaXx2".to_string()));
+        assert_eq!(Err(ChemError::UnknownElement { symbol: "Xx".to_string(), span: 2..4 }), result);
+    }
+
+    #[test]
+    fn unclosed_parenthesis_error_span_test() {
+        let result = parse_molecule(&test_atoms::atoms_map(), &tokenize(&"Se(CH3O".to_string()));
+        assert!(result.is_err());
+        assert_eq!(Some(2..3), result.unwrap_err().span());
+    }
+
     #[test]
     fn parse_se_ch3_2_o_test() {
         let se_c2_h6_o = BTreeMap::from([
@@ -111,6 +125,74 @@ mod parsing_tests {
         expect_molecule_parsing_success("C(CH3^-)4", c_ch3_4, -4)
     }
 
+    #[test]
+    fn parse_na2co3_10h2o_hydrate_test() {
+        let na2co3_10h2o = BTreeMap::from([
+            (test_atoms::sodium(), 2),
+            (test_atoms::carbon(), 1),
+            (test_atoms::oxygen(), 13),
+            (test_atoms::hydrogen(), 20)
+        ]);
+        expect_molecule_parsing_success("Na2CO3·10H2O", na2co3_10h2o.clone(), 0);
+        expect_molecule_parsing_success("Na2CO3*10H2O", na2co3_10h2o.clone(), 0);
+        expect_molecule_parsing_success("Na2CO3.10H2O", na2co3_10h2o, 0);
+    }
+
+    #[test]
+    fn adduct_multiplier_overflow_test() {
+        let result = parse_molecule(&test_atoms::atoms_map(), &tokenize(&"CuSO4·99999999999H2O".to_string()));
+        assert_eq!(Err(ChemError::UnexpectedToken { token: "99999999999".to_string(), span: 6..17 }), result);
+        let result = parse_molecule(&test_atoms::atoms_map(), &tokenize(&"CuO·4000000000H2O".to_string()));
+        assert_eq!(Err(ChemError::UnexpectedToken { token: "4000000000".to_string(), span: 4..14 }), result);
+    }
+
+    #[test]
+    fn parse_adduct_without_multiplier_test() {
+        let adduct = BTreeMap::from([
+            (test_atoms::copper(), 1),
+            (test_atoms::oxygen(), 2),
+            (test_atoms::hydrogen(), 5),
+            (test_atoms::nitrogen(), 1)
+        ]);
+        expect_molecule_parsing_success("Cu(OH)2·NH3", adduct, 0);
+    }
+
+    #[test]
+    fn hydrate_display_test() {
+        let parsed = parse_molecule(&test_atoms::atoms_map(), &tokenize(&"Na2CO3·10H2O".to_string()));
+        assert_eq!("Na2CO3·10H2O", parsed.unwrap().to_string());
+    }
+
+    #[test]
+    fn dangling_adduct_separator_test() {
+        let result = parse_molecule(&test_atoms::atoms_map(), &tokenize(&"Na2CO3*".to_string()));
+        assert_eq!(Err(ChemError::UnexpectedToken { token: "*".to_string(), span: 6..7 }), result);
+    }
+
+    #[test]
+    fn parse_electron_test() {
+        for input in ["e^-", "e-"] {
+            let parsed = parse_molecule(&test_atoms::atoms_map(), &tokenize(&input.to_string())).unwrap();
+            assert!(parsed.is_electron());
+            assert_eq!(-1, parsed.charge);
+            assert_eq!(0, parsed.mass_nano_amu());
+            assert_eq!(input, parsed.to_string());
+        }
+        expect_molecule_parsing_failure("e^+");
+        expect_molecule_parsing_failure("e");
+    }
+
+    #[test]
+    fn electrons_mass_conversion_test() {
+        let tokens = tokenize(&"1 mol O2 + 4 mol H^+ + 1 g e^- => H2O".to_string());
+        let quant_eq = parse_quantified_equation(&test_atoms::atoms_map(), &tokens).unwrap();
+        let (electron, mass) = quant_eq.lhs[2].clone();
+        let expected = ChemError::UnitConversion { from: ChemUnit::GRAM, to: ChemUnit::MOL, species: "e^-".to_string() };
+        assert_eq!(Some(expected.clone()), convert(&electron, &mass.unwrap(), ChemUnit::MOL).err());
+        assert_eq!(Some(expected), compute_lhs_coefs(&quant_eq, &GasConditions::STP).err());
+        assert!(convert(&electron, &ChemQuantity(1.0, ChemUnit::MOL, 0.0), ChemUnit::GRAM).is_err());
+    }
+
     #[test]
     fn parse_respiration_equation_test() {
         let eq_str = "C6H12O6 + O2 => H2O + CO2".to_string();
@@ -125,6 +207,29 @@ mod parsing_tests {
         assert_eq!(expected, actual_res.unwrap());
     }
 
+    #[test]
+    fn parse_equation_with_phases_test() {
+        let eq_str = "C6H12O6(s) + O2(g) => H2O(l) + CO2(g)".to_string();
+        let with_phase = |molec: Molecule, phase: Phase| { Molecule { phase: Some(phase), ..molec } };
+        let expected = RawEquation {
+            lhs: Vec::from([with_phase(c6h12o6(), Phase::Solid), with_phase(o2(), Phase::Gas)]),
+            rhs: Vec::from([with_phase(h2o(), Phase::Liquid), with_phase(co2(), Phase::Gas)]),
+            arrow: "=>".to_string(),
+        };
+        let actual = parse_raw_equation(&test_atoms::atoms_map(), &tokenize(&eq_str)).unwrap();
+        assert_eq!(expected, actual);
+        assert_eq!(eq_str, actual.to_string());
+    }
+
+    #[test]
+    fn parse_quantified_equation_with_phases_test() {
+        let eq_str = "2.3 mol C6H12O6(aq) + O2(g) => H2O(l) + CO2(g)".to_string();
+        let actual = parse_quantified_equation(&test_atoms::atoms_map(), &tokenize(&eq_str)).unwrap();
+        assert_eq!(Some(Phase::Aqueous), actual.lhs[0].0.phase);
+        assert_eq!("C6H12O6(aq)", actual.lhs[0].0.to_string());
+        assert_eq!(Some(Phase::Liquid), actual.rhs[0].0.phase);
+    }
+
     #[test]
     fn parse_redox_equation_test() {
         let eq_str = "Fe + Cu^2+ => Fe^2+ + Cu".to_string();
@@ -134,6 +239,7 @@ mod parsing_tests {
             ]),
             charge: 0,
             string_repr: Some("Fe".to_string()),
+            phase: None,
         };
         let cu_2plus = Molecule {
             atoms: BTreeMap::from([
@@ -141,6 +247,7 @@ mod parsing_tests {
             ]),
             charge: 2,
             string_repr: Some("Cu^2+".to_string()),
+            phase: None,
         };
         let fe_2plus = Molecule {
             atoms: BTreeMap::from([
@@ -148,6 +255,7 @@ mod parsing_tests {
             ]),
             charge: 2,
             string_repr: Some("Fe^2+".to_string()),
+            phase: None,
         };
         let cu = Molecule {
             atoms: BTreeMap::from([
@@ -155,6 +263,7 @@ mod parsing_tests {
             ]),
             charge: 0,
             string_repr: Some("Cu".to_string()),
+            phase: None,
         };
         let actual_res = parse_raw_equation(&test_atoms::atoms_map(), &tokenize(&eq_str));
         let expected = RawEquation {
@@ -171,12 +280,12 @@ mod parsing_tests {
         let eq_str = "2.3 mol C6H12O6 + O2 => H2O + 1 g CO2".to_string();
         let expected_output = QuantifiedEquation {
             lhs: Vec::from([
-                (c6h12o6(), Some(ChemQuantity(2.3, Mol))),
+                (c6h12o6(), Some(ChemQuantity(2.3, ChemUnit::MOL, 0.0))),
                 (o2(), None)
             ]),
             rhs: Vec::from([
                 (h2o(), None),
-                (co2(), Some(ChemQuantity(1.0, Gram)))
+                (co2(), Some(ChemQuantity(1.0, ChemUnit::GRAM, 0.0)))
             ]),
             arrow: "=>".to_string(),
         };
@@ -196,14 +305,201 @@ mod parsing_tests {
             assert_eq!(exp_molec.clone(), act_molec);
             assert_eq!(exp_quant.is_some(), act_quant.is_some());
             if exp_quant.is_some(){
-                let ChemQuantity(exp_value, exp_unit) = exp_quant.clone().unwrap();
-                let ChemQuantity(act_value, act_unit) = act_quant.clone().unwrap();
+                let ChemQuantity(exp_value, exp_unit, _) = exp_quant.clone().unwrap();
+                let ChemQuantity(act_value, act_unit, _) = act_quant.clone().unwrap();
                 assert_eq!(exp_unit, act_unit);
                 assert_near(exp_value, act_value, 1e-2);
             }
         }
     }
 
+    #[test]
+    fn tokenize_numeric_literal_test() {
+        let actual = tokenize(&"2.5e-3 mol CuSO4.5H2O".to_string());
+        assert_eq!(Token("2.5e-3".to_string(), TokenType::Numeric, 0), actual[0]);
+        assert_eq!(Token("mol".to_string(), TokenType::Alphabetic, 7), actual[2]);
+        // the dot of the hydrate is not part of a numeric literal
+        assert_eq!(Token("4".to_string(), TokenType::Numeric, 15), actual[7]);
+        assert_eq!(Token(".".to_string(), TokenType::Dot, 16), actual[8]);
+    }
+
+    #[test]
+    fn parse_numeric_literal_test() {
+        assert_eq!(Some(2.0), parse_numeric_literal("2"));
+        assert_eq!(Some(0.5), parse_numeric_literal(".5"));
+        assert_eq!(Some(0.0025), parse_numeric_literal("2.5e-3"));
+        assert_eq!(Some(25.0), parse_numeric_literal("2.5E+1"));
+        assert_eq!(Some(1_000_000.0), parse_numeric_literal("1_000_000"));
+        for malformed in ["1.2.3", "-2", "1__000", "_1", "1_", "1e", "1e5e3", "1e400"] {
+            assert_eq!(None, parse_numeric_literal(malformed), "{}", malformed);
+        }
+    }
+
+    #[test]
+    fn parse_quantities_in_scientific_notation_test() {
+        let eq_str = "2.5e-3 mol C6H12O6 + .5 g O2 => H2O + CO2".to_string();
+        let actual = parse_quantified_equation(&test_atoms::atoms_map(), &tokenize(&eq_str)).unwrap();
+        let ChemQuantity(glucose_value, glucose_unit, _) = actual.lhs[0].1.clone().unwrap();
+        assert_eq!(ChemUnit::MOL, glucose_unit);
+        assert_near(0.0025, glucose_value, 1e-12);
+        let ChemQuantity(oxygen_value, oxygen_unit, _) = actual.lhs[1].1.clone().unwrap();
+        assert_eq!(ChemUnit::GRAM, oxygen_unit);
+        assert_near(0.5, oxygen_value, 1e-12);
+    }
+
+    #[test]
+    fn parse_prefixed_and_multi_token_units_test() {
+        let eq_str = "1 kg C6H12O6 + 250 mL O2 + 2 cm³ H2O + 3 m3 CO2 => H2O".to_string();
+        let actual = parse_quantified_equation(&test_atoms::atoms_map(), &tokenize(&eq_str)).unwrap();
+        let units: Vec<String> = actual.lhs.iter().map(|(_, qty)| { qty.clone().unwrap().1.to_string() }).collect();
+        assert_eq!(Vec::from(["kg", "mL", "cm³", "m³"]), units);
+        assert_eq!(Vec::from([c6h12o6(), o2(), h2o(), co2()]), actual.lhs.iter().map(|(molec, _)| { molec.clone() }).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn parse_solution_quantities_test() {
+        let eq_str = "25 mL 0.1 M C6H12O6 + 50 g 10% O2 + 2 L 9 g/L H2O => CO2".to_string();
+        let actual = parse_quantified_equation(&test_atoms::atoms_map(), &tokenize(&eq_str)).unwrap();
+        let quantities: Vec<(f64, ChemUnit)> = actual.lhs.iter().map(|(_, qty)| {
+            let ChemQuantity(value, unit, _) = qty.clone().unwrap();
+            (value, unit)
+        }).collect();
+        assert_eq!(Vec::from([ChemUnit::MOL, ChemUnit::GRAM, ChemUnit::GRAM]), quantities.iter().map(|(_, unit)| { *unit }).collect::<Vec<_>>());
+        assert_near(0.0025, quantities[0].0, 1e-12);
+        assert_near(5.0, quantities[1].0, 1e-12);
+        assert_near(18.0, quantities[2].0, 1e-12);
+        assert_eq!(Vec::from([c6h12o6(), o2(), h2o()]), actual.lhs.iter().map(|(molec, _)| { molec.clone() }).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn concentration_of_wrong_quantity_of_solution_test() {
+        let result = parse_quantified_equation(&test_atoms::atoms_map(), &tokenize(&"O2 + 50 g 0.1 M C6H12O6 => CO2".to_string()));
+        assert_eq!(
+            Some(ChemError::SolutionQuantity { solution: "g".to_string(), concentration: "M".to_string(), span: 5..15 }),
+            result.err()
+        );
+    }
+
+    #[test]
+    fn parse_quantity_test() {
+        let ChemQuantity(value, unit, _) = parse_quantity(&tokenize(&" 50 mL".to_string())).unwrap();
+        assert_near(50.0, value, 1e-12);
+        assert_eq!("mL", unit.to_string());
+        assert_eq!(
+            Some(ChemError::QuantityFormat { quantity: "mL".to_string(), span: 1..3 }),
+            parse_quantity(&tokenize(&" mL".to_string())).err()
+        );
+    }
+
+    #[test]
+    fn parse_gas_conditions_test() {
+        let conditions = parse_gas_conditions(&tokenize(&"298 K, 1 atm".to_string())).unwrap();
+        assert_near(298.0, conditions.temperature, 1e-9);
+        assert_near(101_325.0, conditions.pressure, 1e-9);
+        let conditions = parse_gas_conditions(&tokenize(&"2 bar, -10 °C".to_string())).unwrap();
+        assert_near(263.15, conditions.temperature, 1e-9);
+        assert_near(200_000.0, conditions.pressure, 1e-9);
+        let conditions = parse_gas_conditions(&tokenize(&"25 °C".to_string())).unwrap();
+        assert_eq!(GasConditions { temperature: 298.15, ..GasConditions::STP }, conditions);
+        assert_eq!(
+            Some(ChemError::ConditionsFormat { conditions: "3 atm".to_string(), span: 7..12 }),
+            parse_gas_conditions(&tokenize(&"2 atm, 3 atm".to_string())).err()
+        );
+        assert_eq!(
+            Some(ChemError::ConditionsFormat { conditions: "-300 °C".to_string(), span: 0..7 }),
+            parse_gas_conditions(&tokenize(&"-300 °C".to_string())).err()
+        );
+    }
+
+    #[test]
+    fn split_reaction_conditions_test() {
+        for txt in ["H2 + O2 => H2O at 25 °C in 50 mL", "H2 + O2 => H2O in 50 mL at 25 °C"] {
+            let (eq_txt, conditions) = split_reaction_conditions(txt).unwrap();
+            assert_eq!("H2 + O2 => H2O", eq_txt);
+            assert_eq!(GasConditions { temperature: 298.15, ..GasConditions::STP }, conditions.gases);
+            let volume = conditions.final_volume.unwrap();
+            assert_near(0.05, volume.0 * volume.1.reference_factor(), 1e-12);
+        }
+        let (eq_txt, conditions) = split_reaction_conditions("H2 + O2 => H2O").unwrap();
+        assert_eq!("H2 + O2 => H2O", eq_txt);
+        assert_eq!(GasConditions::STP, conditions.gases);
+        assert!(conditions.final_volume.is_none());
+        assert_eq!(
+            Some(ChemError::QuantityLayout { description: "'at' given twice".to_string(), span: Some(15..23) }),
+            split_reaction_conditions("H2 + O2 => H2O at 25 °C in 1 L at 1 atm").err()
+        );
+        assert_eq!(
+            Some(ChemError::QuantityLayout { description: "expected a volume of solution after 'in', e.g. '50 mL'".to_string(), span: Some(18..22) }),
+            split_reaction_conditions("H2 + O2 => H2O in 50 g").err()
+        );
+        assert_eq!(
+            Some(ChemError::QuantityLayout { description: "the volume of solution after 'in' must be positive".to_string(), span: Some(18..22) }),
+            split_reaction_conditions("H2 + O2 => H2O in 0 mL").err()
+        );
+    }
+
+    #[test]
+    fn parse_observed_equation_test() {
+        let tokens = tokenize(&"1 mol H2 + 1 mol O2 => 0.9 mol H2O observed".to_string());
+        let (quant_eq, annotations) = parse_annotated_equation(&test_atoms::atoms_map(), &tokens).unwrap();
+        assert_eq!(Vec::from([0]), annotations.observed_products);
+        assert_near(0.9, quant_eq.rhs[0].1.clone().unwrap().0, 1e-12);
+        assert_eq!(
+            Some(ChemError::QuantityLayout { description: "'observed' must follow a species with a quantity, e.g. '4.1 g H2O observed'".to_string(), span: Some(27..35) }),
+            parse_annotated_equation(&test_atoms::atoms_map(), &tokenize(&"1 mol H2 + 1 mol O2 => H2O observed".to_string())).err()
+        );
+        assert_eq!(
+            Some(ChemError::QuantityLayout { description: "only the quantities of products can be 'observed'".to_string(), span: Some(9..17) }),
+            parse_annotated_equation(&test_atoms::atoms_map(), &tokenize(&"1 mol H2 observed + 1 mol O2 => H2O".to_string())).err()
+        );
+    }
+
+    #[test]
+    fn parse_purity_test() {
+        let tokens = tokenize(&"100 g C6H12O6 @ 92% + O2 @92.5% => H2O + CO2".to_string());
+        let (quant_eq, annotations) = parse_annotated_equation(&test_atoms::atoms_map(), &tokens).unwrap();
+        assert_eq!(Vec::from([Some(0.92), Some(0.925)]), annotations.lhs_purities);
+        assert_eq!(Vec::from([None, None]), annotations.rhs_purities);
+        assert_eq!(c6h12o6(), quant_eq.lhs[0].0);
+        assert_near(92.0, quant_eq.lhs[0].1.clone().unwrap().0, 1e-9);
+        assert!(quant_eq.lhs[1].1.is_none());
+        assert_eq!(
+            Some(ChemError::PurityFormat { purity: "@120%".to_string(), span: 9..15 }),
+            parse_quantified_equation(&test_atoms::atoms_map(), &tokenize(&"100 g O2 @ 120% => O2".to_string())).err()
+        );
+        assert_eq!(
+            Some(ChemError::QuantityLayout { description: "the purity of a species is by mass, it only applies to masses, e.g. '100 g CaCO3 @ 92%'".to_string(), span: Some(0..14) }),
+            parse_quantified_equation(&test_atoms::atoms_map(), &tokenize(&"2 mol O2 @ 92% => O2".to_string())).err()
+        );
+    }
+
+    #[test]
+    fn unknown_unit_test() {
+        let result = parse_quantified_equation(&test_atoms::atoms_map(), &tokenize(&"2 kparticles O2 => O2".to_string()));
+        assert_eq!(Some(ChemError::UnknownUnit { unit: "kparticles".to_string(), span: 2..12 }), result.err());
+    }
+
+    #[test]
+    fn malformed_quantity_test() {
+        let result = parse_quantified_equation(&test_atoms::atoms_map(), &tokenize(&"O2 + 1.2.3 g C6H12O6 => H2O + CO2".to_string()));
+        assert_eq!(Some(ChemError::QuantityFormat { quantity: "1.2.3".to_string(), span: 5..10 }), result.err());
+        let result = parse_quantified_equation(&test_atoms::atoms_map(), &tokenize(&"-2 g O2 => H2O".to_string()));
+        assert_eq!(Some(ChemError::QuantityFormat { quantity: "-2".to_string(), span: 0..2 }), result.err());
+    }
+
+    #[test]
+    fn locate_unconserved_elements_test() {
+        let tokens = tokenize(&"NaO2 + H2 => H2O + CO2".to_string());
+        let raw_eq_res = parse_raw_equation(&test_atoms::atoms_map(), &tokens);
+        assert!(raw_eq_res.is_ok());
+        let located = locate_unconserved_elements(&tokens, &unconserved_elements(&raw_eq_res.unwrap()));
+        let expected = Vec::from([
+            UnconservedElement { atom: test_atoms::sodium(), side: EquationSide::Reactants, position: Some(0) },
+            UnconservedElement { atom: test_atoms::carbon(), side: EquationSide::Products, position: Some(19) }
+        ]);
+        assert_eq!(expected, located);
+    }
+
     fn expect_molecule_parsing_success(input: &str, expected_atoms: BTreeMap<Atom, u32>, expected_charge: i32) {
         let parsed = parse_molecule(
             &test_atoms::atoms_map(),
@@ -0,0 +1,182 @@
+use std::collections::BTreeMap;
+use std::fs;
+use num_rational::Rational64;
+use Stoichio_calc::chemistry::{balance, Medium, Molecule, PeriodicTable};
+use Stoichio_calc::data_loading::load_elements_properties;
+use Stoichio_calc::elements::ElementProperties;
+use Stoichio_calc::parsing::{parse_molecule, parse_raw_equation, tokenize};
+use Stoichio_calc::error::ChemError;
+use Stoichio_calc::redox::{format_oxidation_state, half_reactions, HalfReactions, oxidation_states, redox_report, RedoxReport};
+
+fn load_elements() -> Vec<ElementProperties> {
+    load_elements_properties(fs::read_to_string("./res/periodic_table.csv").unwrap().as_str()).unwrap()
+}
+
+fn periodic_table_of(elements: &[ElementProperties]) -> PeriodicTable {
+    elements.iter().map(|elem| { (elem.atom.code.clone(), elem.atom.clone()) }).collect()
+}
+
+/// Returns the oxidation states of the molecule, formatted and indexed by symbol
+fn formatted_oxidation_states(molecule_str: &str) -> BTreeMap<String, String> {
+    let elements = load_elements();
+    let periodic_table = periodic_table_of(&elements);
+    let molecule = parse_molecule(&periodic_table, &tokenize(&molecule_str.to_string())).unwrap();
+    oxidation_states(&molecule, &elements).iter()
+        .map(|(atom, state)| { (atom.code.clone(), format_oxidation_state(state)) })
+        .collect()
+}
+
+fn expected_states(states: &[(&str, &str)]) -> BTreeMap<String, String> {
+    states.iter().map(|(symbol, state)| { (symbol.to_string(), state.to_string()) }).collect()
+}
+
+#[test]
+fn oxygen_and_hydrogen_default_states_test(){
+    assert_eq!(expected_states(&[("C", "+4"), ("O", "-2")]), formatted_oxidation_states("CO2"));
+    assert_eq!(expected_states(&[("C", "-4"), ("H", "+1")]), formatted_oxidation_states("CH4"));
+    assert_eq!(expected_states(&[("K", "+1"), ("Mn", "+7"), ("O", "-2")]), formatted_oxidation_states("KMnO4"));
+}
+
+#[test]
+fn states_sum_up_to_charge_test(){
+    assert_eq!(expected_states(&[("Cr", "+6"), ("O", "-2")]), formatted_oxidation_states("Cr2O7^2-"));
+    assert_eq!(expected_states(&[("H", "+1"), ("N", "-3")]), formatted_oxidation_states("NH4^+"));
+    assert_eq!(expected_states(&[("Fe", "+3")]), formatted_oxidation_states("Fe^3+"));
+    assert_eq!(expected_states(&[("H", "0")]), formatted_oxidation_states("H2"));
+}
+
+#[test]
+fn peroxide_and_hydride_test(){
+    assert_eq!(expected_states(&[("H", "+1"), ("O", "-1")]), formatted_oxidation_states("H2O2"));
+    assert_eq!(expected_states(&[("H", "-1"), ("Na", "+1")]), formatted_oxidation_states("NaH"));
+    assert_eq!(expected_states(&[("F", "-1"), ("O", "+2")]), formatted_oxidation_states("OF2"));
+}
+
+#[test]
+fn fractional_states_test(){
+    assert_eq!(expected_states(&[("Fe", "+8/3"), ("O", "-2")]), formatted_oxidation_states("Fe3O4"));
+    assert_eq!(expected_states(&[("K", "+1"), ("O", "-1/2")]), formatted_oxidation_states("KO2"));
+}
+
+#[test]
+fn electronegativity_ordering_test(){
+    assert_eq!(expected_states(&[("Cl", "-1"), ("I", "+1")]), formatted_oxidation_states("ICl"));
+    assert_eq!(expected_states(&[("Cu", "+2"), ("O", "-2"), ("S", "+6")]), formatted_oxidation_states("CuSO4"));
+    assert_eq!(expected_states(&[("C", "-2"), ("Cl", "-1"), ("H", "+1")]), formatted_oxidation_states("CH3Cl"));
+}
+
+#[test]
+fn several_nonmetals_test(){
+    // the metal is left a positive state, the least electronegative nonmetal takes the rest
+    assert_eq!(expected_states(&[("C", "+2"), ("Fe", "+2"), ("K", "+1"), ("N", "-3")]), formatted_oxidation_states("K4Fe(CN)6"));
+    assert_eq!(expected_states(&[("C", "+4"), ("N", "-3"), ("S", "-2")]), formatted_oxidation_states("SCN^-"));
+    assert_eq!(expected_states(&[("Cl", "-1"), ("O", "-2"), ("S", "+6")]), formatted_oxidation_states("SO2Cl2"));
+}
+
+fn report_for(equation_str: &str) -> RedoxReport {
+    let elements = load_elements();
+    let raw_eq = parse_raw_equation(&periodic_table_of(&elements), &tokenize(&equation_str.to_string())).unwrap();
+    redox_report(&balance(&raw_eq).unwrap(), &elements)
+}
+
+/// Formats the changes as `<symbol> <before> <after>`, and the agents as their string representations
+fn summary_of(report: &RedoxReport) -> (Vec<String>, Vec<String>, Vec<String>) {
+    let changes = report.changes.iter()
+        .map(|change| {
+            format!("{} {} {}", change.atom.code, format_oxidation_state(&change.before), format_oxidation_state(&change.after))
+        })
+        .collect();
+    let agents_strs = |agents: &Vec<Molecule>| -> Vec<String> { agents.iter().map(|molec| { molec.to_string() }).collect() };
+    (changes, agents_strs(&report.oxidizing_agents), agents_strs(&report.reducing_agents))
+}
+
+#[test]
+fn redox_report_test(){
+    let report = report_for("MnO4^- + Fe^2+ + H^+ => Mn^2+ + Fe^3+ + H2O");
+    assert_eq!(
+        (vec!["Fe +2 +3".to_string(), "Mn +7 +2".to_string()], vec!["MnO4^-".to_string()], vec!["Fe^2+".to_string()]),
+        summary_of(&report)
+    );
+    assert_eq!(Rational64::from(5), report.n_electrons);
+}
+
+#[test]
+fn redox_report_with_spectator_part_test(){
+    let report = report_for("Cu + HNO3 => Cu(NO3)2 + NO + H2O");
+    assert_eq!(
+        (vec!["Cu 0 +2".to_string(), "N +5 +2".to_string()], vec!["HNO3".to_string()], vec!["Cu".to_string()]),
+        summary_of(&report)
+    );
+    assert_eq!(Rational64::from(6), report.n_electrons);
+}
+
+#[test]
+fn redox_report_with_cyanide_test(){
+    let report = report_for("K4Fe(CN)6 + KMnO4 + H2SO4 => KHSO4 + Fe2(SO4)3 + MnSO4 + HNO3 + CO2 + H2O");
+    assert_eq!(
+        (
+            vec!["C +2 +4".to_string(), "Fe +2 +3".to_string(), "Mn +7 +2".to_string(), "N -3 +5".to_string()],
+            vec!["KMnO4".to_string()],
+            vec!["K4Fe(CN)6".to_string()]
+        ),
+        summary_of(&report)
+    );
+    // 122 Mn gain 5 electrons each, lost by 60 C, 10 Fe and 60 N
+    assert_eq!(Rational64::from(610), report.n_electrons);
+}
+
+#[test]
+fn disproportionation_report_test(){
+    let report = report_for("Cl2 + OH^- => Cl^- + ClO3^- + H2O");
+    assert_eq!(
+        (vec!["Cl 0 -1".to_string(), "Cl 0 +5".to_string()], vec!["Cl2".to_string()], vec!["Cl2".to_string()]),
+        summary_of(&report)
+    );
+    assert_eq!(Rational64::from(5), report.n_electrons);
+}
+
+#[test]
+fn non_redox_report_test(){
+    let report = report_for("NaOH + HCl => NaCl + H2O");
+    assert!(!report.is_redox());
+    assert_eq!(Rational64::from(0), report.n_electrons);
+}
+
+fn half_reactions_for(equation_str: &str, medium: Option<Medium>) -> Result<HalfReactions, ChemError> {
+    let elements = load_elements();
+    let periodic_table = periodic_table_of(&elements);
+    let raw_eq = parse_raw_equation(&periodic_table, &tokenize(&equation_str.to_string())).unwrap();
+    half_reactions(&raw_eq, medium, &periodic_table, &elements)
+}
+
+#[test]
+fn half_reactions_test(){
+    let half_reactions = half_reactions_for("MnO4^- + Fe^2+ + H^+ => Mn^2+ + Fe^3+ + H2O", None).unwrap();
+    assert_eq!("Fe^2+ => Fe^3+ + e^-", half_reactions.oxidation.to_string());
+    assert_eq!("MnO4^- + 8 H^+ + 5 e^- => Mn^2+ + 4 H2O", half_reactions.reduction.to_string());
+    assert_eq!((5, 1), (half_reactions.oxidation_multiplier, half_reactions.reduction_multiplier));
+    assert_eq!("MnO4^- + 5 Fe^2+ + 8 H^+ => Mn^2+ + 5 Fe^3+ + 4 H2O", half_reactions.equation.to_string());
+}
+
+#[test]
+fn half_reactions_in_medium_test(){
+    let half_reactions = half_reactions_for("MnO4^- + I^- => MnO2 + I2", Some(Medium::Basic)).unwrap();
+    let expected = "oxidation: 2 I^- => I2 + 2 e^- (x3)\n\
+                    reduction: MnO4^- + 2 H2O + 3 e^- => MnO2 + 4 OH^- (x2)\n\
+                    overall: 2 MnO4^- + 6 I^- + 4 H2O => 2 MnO2 + 3 I2 + 8 OH^-";
+    assert_eq!(expected, half_reactions.to_string());
+}
+
+#[test]
+fn disproportionation_half_reactions_test(){
+    let half_reactions = half_reactions_for("Cl2 + OH^- => Cl^- + ClO3^- + H2O", None).unwrap();
+    assert_eq!("Cl2 + 12 OH^- => 2 ClO3^- + 6 H2O + 10 e^-", half_reactions.oxidation.to_string());
+    assert_eq!("Cl2 + 2 e^- => 2 Cl^-", half_reactions.reduction.to_string());
+    assert_eq!((1, 5), (half_reactions.oxidation_multiplier, half_reactions.reduction_multiplier));
+    assert_eq!("3 Cl2 + 6 OH^- => 5 Cl^- + ClO3^- + 3 H2O", half_reactions.equation.to_string());
+}
+
+#[test]
+fn non_redox_half_reactions_test(){
+    assert!(matches!(half_reactions_for("NaOH + HCl => NaCl + H2O", None), Err(ChemError::EquationLayout { .. })));
+}
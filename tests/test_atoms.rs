@@ -6,7 +6,8 @@ pub fn hydrogen() -> Atom {
     Atom {
         name: "hydrogen".to_string(),
         code: "H".to_string(),
-        atomic_mass_milli_amu: 1_007,
+        atomic_mass_nano_amu: 1_007_940_000,
+        atomic_mass_uncertainty_nano_amu: 70_000,
     }
 }
 
@@ -15,7 +16,8 @@ pub fn carbon() -> Atom {
     Atom {
         name: "carbon".to_string(),
         code: "C".to_string(),
-        atomic_mass_milli_amu: 12_011,
+        atomic_mass_nano_amu: 12_010_700_000,
+        atomic_mass_uncertainty_nano_amu: 800_000,
     }
 }
 
@@ -24,7 +26,8 @@ pub fn oxygen() -> Atom {
     Atom {
         name: "oxygen".to_string(),
         code: "O".to_string(),
-        atomic_mass_milli_amu: 15_999,
+        atomic_mass_nano_amu: 15_999_400_000,
+        atomic_mass_uncertainty_nano_amu: 300_000,
     }
 }
 
@@ -33,7 +36,8 @@ pub fn nitrogen() -> Atom {
     Atom {
         name: "nitrogen".to_string(),
         code: "N".to_string(),
-        atomic_mass_milli_amu: 14_007,
+        atomic_mass_nano_amu: 14_006_700_000,
+        atomic_mass_uncertainty_nano_amu: 200_000,
     }
 }
 
@@ -42,7 +46,8 @@ pub fn sodium() -> Atom {
     Atom {
         name: "sodium".to_string(),
         code: "Na".to_string(),
-        atomic_mass_milli_amu: 22_990,
+        atomic_mass_nano_amu: 22_989_769_280,
+        atomic_mass_uncertainty_nano_amu: 20,
     }
 }
 
@@ -51,7 +56,8 @@ pub fn selenium() -> Atom {
     Atom {
         name: "selenium".to_string(),
         code: "Se".to_string(),
-        atomic_mass_milli_amu: 78_960,
+        atomic_mass_nano_amu: 78_960_000_000,
+        atomic_mass_uncertainty_nano_amu: 30_000_000,
     }
 }
 
@@ -60,7 +66,8 @@ pub fn rubidium() -> Atom {
     Atom {
         name: "rubidium".to_string(),
         code: "Rb".to_string(),
-        atomic_mass_milli_amu: 85_468
+        atomic_mass_nano_amu: 85_467_800_000,
+        atomic_mass_uncertainty_nano_amu: 300_000
     }
 }
 
@@ -69,7 +76,8 @@ pub fn plutonium() -> Atom {
     Atom {
         name: "plutonium".to_string(),
         code: "Pu".to_string(),
-        atomic_mass_milli_amu: 244_000
+        atomic_mass_nano_amu: 244_000_000_000,
+        atomic_mass_uncertainty_nano_amu: 0
     }
 }
 
@@ -78,7 +86,8 @@ pub fn iron() -> Atom {
     Atom {
         name: "iron".to_string(),
         code: "Fe".to_string(),
-        atomic_mass_milli_amu: 55_845
+        atomic_mass_nano_amu: 55_845_000_000,
+        atomic_mass_uncertainty_nano_amu: 2_000_000
     }
 }
 
@@ -87,7 +96,8 @@ pub fn copper() -> Atom {
     Atom {
         name: "Copper".to_string(),
         code: "Cu".to_string(),
-        atomic_mass_milli_amu: 63_546
+        atomic_mass_nano_amu: 63_546_000_000,
+        atomic_mass_uncertainty_nano_amu: 3_000_000
     }
 }
 
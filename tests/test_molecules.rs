@@ -8,7 +8,8 @@ pub fn h2o() -> Molecule {
     Molecule {
         atoms: BTreeMap::from([(test_atoms::hydrogen(), 2), (test_atoms::oxygen(), 1)]),
         charge: 0,
-        string_repr: Some("H2O".to_string())
+        string_repr: Some("H2O".to_string()),
+        phase: None
     }
 }
 
@@ -16,7 +17,8 @@ pub fn co2() -> Molecule {
     Molecule {
         atoms: BTreeMap::from([(test_atoms::carbon(), 1), (test_atoms::oxygen(), 2)]),
         charge: 0,
-        string_repr: Some("CO2".to_string())
+        string_repr: Some("CO2".to_string()),
+        phase: None
     }
 }
 
@@ -24,7 +26,8 @@ pub fn o2() -> Molecule {
     Molecule {
         atoms: BTreeMap::from([(test_atoms::oxygen(), 2)]),
         charge: 0,
-        string_repr: Some("O2".to_string())
+        string_repr: Some("O2".to_string()),
+        phase: None
     }
 }
 
@@ -36,7 +39,8 @@ pub fn c6h12o6() -> Molecule {
             (test_atoms::oxygen(), 6)
         ]),
         charge: 0,
-        string_repr: Some("C6H12O6".to_string())
+        string_repr: Some("C6H12O6".to_string()),
+        phase: None
     }
 }
 